@@ -0,0 +1,654 @@
+// このファイルは `reversi header` によって生成される（直接編集しない）
+
+#ifndef REVERSI_AI_H
+#define REVERSI_AI_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct EngineConfig EngineConfig;
+typedef struct Ponder Ponder;
+typedef struct SearchEngine SearchEngine;
+
+/// 探索の途中経過を受け取る C の関数
+///
+/// 探索している深さ、これまでに見つかった最良の手のビット番号、その評価値、探索した節点の数、呼び出し側が渡したポインタを受け取る
+typedef void (*ProgressCallback)(int32_t depth, int32_t best_move, int32_t score, uint64_t nodes, void *user_data);
+
+/// 窓付きの完全探索の結果が、石数の差が窓の下限より小さいことを表す戻り値
+#define REVERSI_WINDOW_FAIL_LOW (-1)
+
+/// 窓付きの完全探索の結果が、石数の差が窓の中にあることを表す戻り値
+#define REVERSI_WINDOW_EXACT (0)
+
+/// 窓付きの完全探索の結果が、石数の差が窓の上限より大きいことを表す戻り値
+#define REVERSI_WINDOW_FAIL_HIGH (1)
+
+/// 検査付きの関数が成功したことを表す戻り値
+#define REVERSI_OK (0)
+
+/// ビット番号や座標が盤の範囲外であることを表す戻り値
+#define REVERSI_ERROR_INVALID_INDEX (-1)
+
+/// 2つのビットボードに共通のマスがあることを表す戻り値
+#define REVERSI_ERROR_OVERLAPPING_BOARDS (-2)
+
+/// 先読みの深さが不正であることを表す戻り値
+#define REVERSI_ERROR_INVALID_DEPTH (-3)
+
+/// 並列数が 1 未満であることを表す戻り値
+#define REVERSI_ERROR_INVALID_CONCURRENCY (-4)
+
+/// 打てない場所に打とうとしたことを表す戻り値
+#define REVERSI_ERROR_ILLEGAL_MOVE (-5)
+
+/// 結果を書き込むポインタが NULL であることを表す戻り値
+#define REVERSI_ERROR_NULL_POINTER (-6)
+
+/// ライブラリの内部でパニックが起きたことを表す戻り値
+#define REVERSI_ERROR_PANIC (-7)
+
+/// 探索の窓の下限が上限より大きいことを表す戻り値
+#define REVERSI_ERROR_INVALID_WINDOW (-8)
+
+/// 根の手をそれぞれ全幅の窓で探索する根の探索の戦略（既定）
+#define REVERSI_STRATEGY_FULL_WINDOW (0)
+
+/// 反復深化で1つ浅い探索の評価値を中心とする狭い窓で探索し、窓を外れた場合は窓を広げて探索し直す根の探索の戦略
+#define REVERSI_STRATEGY_ASPIRATION (1)
+
+/// 反復深化で1つ浅い探索の評価値から始めて、幅のない窓の探索を繰り返して評価値を絞り込む根の探索の戦略（MTD(f)）
+///
+/// 部分探索は置換表を持たないので、全幅の窓で探索するよりも遅い
+#define REVERSI_STRATEGY_MTDF (2)
+
+/// C から呼び出せる関数の互換性を表す ABI のバージョン
+///
+/// 既存の関数のシグネチャや意味を互換性のない形で変更した場合に増やす（関数や定数の追加では増やさない）
+#define REVERSI_ABI_VERSION (1)
+
+/// 複数スレッドによる並列探索に対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_PARALLEL_SEARCH (0x0001)
+
+/// 時間を制限した探索に対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_TIMED_SEARCH (0x0002)
+
+/// 定石集に対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_BOOK (0x0004)
+
+/// モンテカルロ木探索に対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_MCTS (0x0008)
+
+/// 相手の手番での先読みに対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_PONDER (0x0010)
+
+/// 設定のハンドルに対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_CONFIG (0x0020)
+
+/// 状態を持つエンジンのハンドルと探索の中断に対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_ENGINE (0x0040)
+
+/// 入力を検査してエラーの戻り値を返す関数に対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_CHECKED (0x0080)
+
+/// 探索の途中経過を受け取るコールバックに対応していることを表す機能ビット
+#define REVERSI_CAPABILITY_PROGRESS (0x0100)
+
+/// ビット番号から i 座標を返す
+int32_t index_to_position_i(int32_t n);
+
+/// ビット番号から j 座標を返す
+int32_t index_to_position_j(int32_t n);
+
+/// 座標位置からビット番号を返す
+int32_t position_to_index(int32_t i, int32_t j);
+
+/// n 番目にだけビットを立たせたビットボード表現を返す
+///
+/// n が 0 以上 64 未満でない場合は 0 を返す
+uint64_t index_to_bit(int32_t n);
+
+/// 特定の座標位置に対応する場所にだけビットを立たせたビットボード表現を返す
+uint64_t position_to_bit(int32_t i, int32_t j);
+
+/// myself プレイヤーが着手可能な手のビットボード表現を返す
+uint64_t possible_moves(uint64_t myself, uint64_t opponent);
+
+/// myself プレイヤーが index 地点に打ったときに返せる石のビットボード表現を返す
+uint64_t turnovers(uint64_t myself, uint64_t opponent, int32_t index);
+
+/// myself プレイヤーが index 地点に打てるかどうかを返す
+int32_t can_place(uint64_t myself, uint64_t opponent, int32_t index);
+
+/// myself プレイヤーが index 地点に打ったときに得られる盤を可変参照によって変更し、返した石のビットボード表現を戻り値で返す
+uint64_t place(uint64_t myself, uint64_t opponent, int32_t index, uint64_t *nmyself, uint64_t *nopponent);
+
+/// 立っているビットの数を返す
+int32_t count_bits(uint64_t n);
+
+/// myself プレイヤーの石の数から opponent プレイヤーの石の数を引いたものを返す
+int32_t balance(uint64_t myself, uint64_t opponent);
+
+/// 有利なほど大きいように盤上の位置ごとにつけられた重みを用いて、石のある位置の重みの和を返す
+int32_t sum_of_weights(uint64_t disks);
+
+/// myself プレイヤーに有利なほど大きな数が返る静的評価関数
+int32_t evaluation(uint64_t myself, uint64_t opponent);
+
+/// turns 周りの開放度を返す
+int32_t openness(uint64_t myself, uint64_t opponent, uint64_t turns);
+
+/// 係数を掛けた開放度の負値を返す
+int32_t openness_evaluation(uint64_t myself, uint64_t opponent, uint64_t turns);
+
+/// ミニマックス戦略に基づいてゲーム木の完全探索をし、最良の手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+int32_t full_search(uint64_t myself, uint64_t opponent);
+
+/// ミニマックス戦略に基づいてゲーム木の完全探索をし、最良の手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
+/// この関数は複数スレッドによって並列処理される
+int32_t full_search_parallel_with(uint64_t myself, uint64_t opponent, int32_t concurrency);
+
+/// ミニマックス戦略に基づいてゲーム木の完全探索をし、最良の手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+int32_t full_search_parallel(uint64_t myself, uint64_t opponent);
+
+/// 終局時の石数の差が lower 以上 upper 以下の範囲にあるかを完全探索で調べ、結果の種類を返す
+///
+/// 石数の差が窓の中にある場合は REVERSI_WINDOW_EXACT を返し、score にその値を書き込む
+///
+/// 窓の下限より小さい場合は REVERSI_WINDOW_FAIL_LOW を返し、score に石数の差の上界を書き込む
+///
+/// 窓の上限より大きい場合は REVERSI_WINDOW_FAIL_HIGH を返し、score に石数の差の下界を書き込む
+///
+/// lower が upper より大きい場合は REVERSI_ERROR_INVALID_WINDOW を返し、どこにも書き込まない
+///
+/// # Safety
+///
+/// score は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t full_search_window(uint64_t myself, uint64_t opponent, int32_t lower, int32_t upper, int32_t *score);
+
+/// ミニマックス戦略に基づいてゲーム木の部分探索をし、最良と思われる手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
+/// depth は先読みの深さで、1 以上である必要があり奇数が望ましい
+int32_t heuristic_search(uint64_t myself, uint64_t opponent, int32_t depth);
+
+/// ミニマックス戦略に基づいてゲーム木の部分探索をし、最良と思われる手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
+/// depth は先読みの深さで、1 以上である必要があり奇数が望ましい
+///
+/// この関数は複数スレッドによって並列処理される
+int32_t heuristic_search_parallel_with(uint64_t myself, uint64_t opponent, int32_t depth, int32_t concurrency);
+
+/// ミニマックス戦略に基づいてゲーム木の部分探索をし、最良と思われる手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
+/// depth は先読みの深さで、1 以上である必要があり奇数が望ましい
+///
+/// この関数は CPU スレッド数のスレッドによって並列処理される
+int32_t heuristic_search_parallel(uint64_t myself, uint64_t opponent, int32_t depth);
+
+/// ミニマックス戦略に基づいてゲーム木の部分探索をし、最良の手との評価値の差が margin 以内の手から無作為に選んだ手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// depth は先読みの深さで、1 以上である必要があり奇数が望ましい
+///
+/// margin が 0 の場合は評価値が最良の手の中から選ぶ（負の margin は 0 として扱う）
+///
+/// 選ばれる手は seed によって決まり、同じ局面と同じ seed に対しては常に同じ手を返す
+int32_t heuristic_search_random(uint64_t myself, uint64_t opponent, int32_t depth, int32_t margin, uint64_t seed);
+
+/// ミニマックス戦略に基づいてゲーム木の探索をし、最良と思われる手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
+/// ゲームの進行度によって部分探索と完全探索を自動で選択する
+///
+/// 切り替えのタイミングと、先読みの深さは数秒で結果が返るような値に調整されている
+int32_t choose_move(uint64_t myself, uint64_t opponent);
+
+/// ミニマックス戦略に基づいてゲーム木の探索をし、最良と思われる手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
+/// ゲームの進行度によって部分探索と完全探索を自動で選択する
+///
+/// 切り替えのタイミングと、先読みの深さは数秒で結果が返るような値に調整されている
+///
+/// この関数は複数スレッドによって並列処理される
+///
+/// 並列処理によって探索にかかる時間が短くなるので非並列版よりも深く読むようにしている
+int32_t choose_move_parallel_with(uint64_t myself, uint64_t opponent, int32_t concurrency);
+
+/// ミニマックス戦略に基づいてゲーム木の探索をし、最良と思われる手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
+/// ゲームの進行度によって部分探索と完全探索を自動で選択する
+///
+/// 切り替えのタイミングと、先読みの深さは数秒で結果が返るような値に調整されている
+///
+/// この関数は CPU スレッド数のスレッドによって並列処理される
+///
+/// 並列処理によって探索にかかる時間が短くなるので非並列版よりも深く読むようにしている
+int32_t choose_move_parallel(uint64_t myself, uint64_t opponent);
+
+/// 手番のプレイヤーから depth 手先までのゲーム木の葉の数を返す
+///
+/// パスも1手として数え、途中で終局した局面はそこで葉として数える
+///
+/// 着手生成の検証や速度の計測に用いる
+uint64_t perft(uint64_t myself, uint64_t opponent, int32_t depth);
+
+/// ミニマックス戦略に基づいてゲーム木の探索をし、最良と思われる手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// ゲームの進行度によって部分探索と完全探索を自動で選択する
+///
+/// 部分探索では最良の手との評価値の差が margin 以内の手から seed に基づいて無作為に選ぶ
+///
+/// 完全探索では choose_move と同じく最良の手を選ぶ
+int32_t choose_move_random(uint64_t myself, uint64_t opponent, int32_t margin, uint64_t seed);
+
+/// 難易度 level に応じた強さで探索をし、選んだ手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// level は 1 から 10 までの整数で、大きいほど強い（範囲外の値は近い方の端に丸められる）
+///
+/// 10 では choose_move と同じ強さで打ち、低い難易度では浅い先読みに加えて評価値への雑音や意図的な悪手が入る
+///
+/// 選ばれる手は seed によって決まり、同じ局面と同じ seed に対しては常に同じ手を返す
+int32_t choose_move_level(uint64_t myself, uint64_t opponent, int32_t level, uint64_t seed);
+
+/// 既定値の設定を作り、そのハンドルを返す
+///
+/// 返されたハンドルは engine_config_free によって解放する必要がある
+EngineConfig *engine_config_new(void);
+
+/// 設定のハンドルを解放する
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+void engine_config_free(EngineConfig *config);
+
+/// 名前が name の設定項目を value にし、設定できた場合は 1 を、名前が不明な場合は 0 を返す
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルで、name は NUL 終端の文字列である必要がある
+int32_t engine_config_set(EngineConfig *config, const char *name, int32_t value);
+
+/// 名前が name の設定項目の値を value に書き込み、書き込めた場合は 1 を、名前が不明な場合は 0 を返す
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドル、name は NUL 終端の文字列、value は書き込み可能な領域を指す必要がある
+int32_t engine_config_get(const EngineConfig *config, const char *name, int32_t *value);
+
+/// 設定 config に従って choose_move と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+int32_t choose_move_with_config(const EngineConfig *config, uint64_t myself, uint64_t opponent);
+
+/// 設定 config に従って choose_move_parallel_with と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+int32_t choose_move_parallel_with_config(const EngineConfig *config, uint64_t myself, uint64_t opponent, int32_t concurrency);
+
+/// 設定 config に従って choose_move_random と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+int32_t choose_move_random_with_config(const EngineConfig *config, uint64_t myself, uint64_t opponent, int32_t margin, uint64_t seed);
+
+/// 設定 config に従って choose_move_level と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+int32_t choose_move_level_with_config(const EngineConfig *config, uint64_t myself, uint64_t opponent, int32_t level, uint64_t seed);
+
+/// 設定 config に従って heuristic_search と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+int32_t heuristic_search_with_config(const EngineConfig *config, uint64_t myself, uint64_t opponent, int32_t depth);
+
+/// 設定 config に従って heuristic_search_parallel_with と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+int32_t heuristic_search_parallel_with_config(const EngineConfig *config, uint64_t myself, uint64_t opponent, int32_t depth, int32_t concurrency);
+
+/// 既定の設定と CPU スレッド数の並列数を持つエンジンを作り、そのハンドルを返す
+///
+/// 返されたハンドルは engine_free によって解放する必要がある
+SearchEngine *engine_new(void);
+
+/// エンジンのハンドルを解放する
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルで、他のスレッドで使われていない必要がある
+void engine_free(SearchEngine *engine);
+
+/// 名前が name の設定項目を value にし、設定できた場合は 1 を、できなかった場合は 0 を返す
+///
+/// 設定項目の名前は engine_config_set と同じものに加えて、探索の並列数を表す "concurrency" を使える
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルで、name は NUL 終端の文字列である必要がある
+int32_t engine_set_option(const SearchEngine *engine, const char *name, int32_t value);
+
+/// エンジンの設定で choose_move_parallel_with と同じ探索をし、手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を、engine_stop によって探索が中断された場合は -2 を返す
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルである必要がある
+int32_t engine_choose_move(const SearchEngine *engine, uint64_t myself, uint64_t opponent);
+
+/// エンジンの設定で choose_move_with_progress と同じ探索をし、手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を、engine_stop によって探索が中断された場合は -2 を返す
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルで、callback は NULL か、user_data を受け取って呼び出せる関数である必要がある
+int32_t engine_choose_move_with_progress(const SearchEngine *engine, uint64_t myself, uint64_t opponent, uint32_t interval_ms, ProgressCallback callback, void *user_data);
+
+/// エンジンの設定で analyze と同じ解析をし、解析した手の数を返す
+///
+/// scores には、ビット番号ごとの手の評価値を長さ 64 の配列に書き込む（打てないマスには INTMIN を書き込む）
+///
+/// engine_stop によって探索が中断された場合は -2 を返し、scores には何も書き込まない
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルで、scores は書き込み可能な長さ 64 の配列を指す必要がある
+int32_t engine_analyze(const SearchEngine *engine, uint64_t myself, uint64_t opponent, int32_t depth, int32_t *scores);
+
+/// 実行中の engine_choose_move または engine_analyze と、既に呼び出されて順番を待っているものの中断を要求する
+///
+/// 探索を実行しているスレッドとは別のスレッドから呼び出す（探索中でない場合は何もしない）
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルである必要がある
+void engine_stop(const SearchEngine *engine);
+
+/// 入力を検査して index_to_bit の結果を out に書き込む
+///
+/// n が 0 以上 64 未満でない場合は REVERSI_ERROR_INVALID_INDEX を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t index_to_bit_checked(int32_t n, uint64_t *out);
+
+/// 入力を検査して position_to_bit の結果を out に書き込む
+///
+/// i と j がともに 0 以上 8 未満でない場合は REVERSI_ERROR_INVALID_INDEX を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t position_to_bit_checked(int32_t i, int32_t j, uint64_t *out);
+
+/// 入力を検査して possible_moves の結果を out に書き込む
+///
+/// myself と opponent が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t possible_moves_checked(uint64_t myself, uint64_t opponent, uint64_t *out);
+
+/// 入力を検査して turnovers の結果を out に書き込む
+///
+/// index が範囲外の場合は REVERSI_ERROR_INVALID_INDEX を、盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t turnovers_checked(uint64_t myself, uint64_t opponent, int32_t index, uint64_t *out);
+
+/// 入力を検査して place と同じく手を打ち、打った後の盤を nmyself と nopponent に、返した石を turns に書き込む
+///
+/// index が範囲外の場合は REVERSI_ERROR_INVALID_INDEX を、盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、index に打てない場合は REVERSI_ERROR_ILLEGAL_MOVE を返す
+///
+/// エラーの場合はどこにも書き込まない
+///
+/// # Safety
+///
+/// nmyself と nopponent は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+///
+/// turns は NULL か、書き込み可能な領域を指す必要がある
+int32_t place_checked(uint64_t myself, uint64_t opponent, int32_t index, uint64_t *nmyself, uint64_t *nopponent, uint64_t *turns);
+
+/// 入力を検査して full_search の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t full_search_checked(uint64_t myself, uint64_t opponent, int32_t *out);
+
+/// 入力を検査して full_search_parallel_with の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、concurrency が 1 未満の場合は REVERSI_ERROR_INVALID_CONCURRENCY を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t full_search_parallel_with_checked(uint64_t myself, uint64_t opponent, int32_t concurrency, int32_t *out);
+
+/// 入力を検査して full_search_window と同じ探索をし、結果の種類を result に、石数の差またはその限界を score に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、lower が upper より大きい場合は REVERSI_ERROR_INVALID_WINDOW を返す
+///
+/// エラーの場合はどこにも書き込まない
+///
+/// # Safety
+///
+/// result と score は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t full_search_window_checked(uint64_t myself, uint64_t opponent, int32_t lower, int32_t upper, int32_t *result, int32_t *score);
+
+/// 入力を検査して heuristic_search の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、depth が 1 未満の場合は REVERSI_ERROR_INVALID_DEPTH を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t heuristic_search_checked(uint64_t myself, uint64_t opponent, int32_t depth, int32_t *out);
+
+/// 入力を検査して heuristic_search_parallel_with の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、depth が 1 未満の場合は REVERSI_ERROR_INVALID_DEPTH を、concurrency が 1 未満の場合は REVERSI_ERROR_INVALID_CONCURRENCY を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t heuristic_search_parallel_with_checked(uint64_t myself, uint64_t opponent, int32_t depth, int32_t concurrency, int32_t *out);
+
+/// 入力を検査して choose_move の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t choose_move_checked(uint64_t myself, uint64_t opponent, int32_t *out);
+
+/// 入力を検査して choose_move_parallel_with の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、concurrency が 1 未満の場合は REVERSI_ERROR_INVALID_CONCURRENCY を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t choose_move_parallel_with_checked(uint64_t myself, uint64_t opponent, int32_t concurrency, int32_t *out);
+
+/// 入力を検査して perft の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、depth が負の場合は REVERSI_ERROR_INVALID_DEPTH を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+int32_t perft_checked(uint64_t myself, uint64_t opponent, int32_t depth, uint64_t *out);
+
+/// UCT に基づくモンテカルロ木探索をし、最も訪問された手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// mcts_choose_move_with_solver で完全探索を用いない場合と同じ
+///
+/// # Safety
+///
+/// visits は NULL か、書き込み可能な長さ 64 の配列を指す必要がある
+int32_t mcts_choose_move(uint64_t myself, uint64_t opponent, uint64_t playouts, uint64_t millis, int32_t concurrency, int32_t guided, uint64_t seed, uint64_t *visits);
+
+/// UCT に基づくモンテカルロ木探索をし、最も訪問された手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// playouts はプレイアウトの回数の上限、millis はミリ秒単位の探索時間の上限で、それぞれ 0 の場合は制限しない（両方 0 の場合は既定の回数で探索する）
+///
+/// guided が 0 でない場合はプレイアウトで静的評価関数を用いる
+///
+/// solver_empties が正の場合は、空きマスがその数以下の局面を完全探索で解いて勝敗を確定させる（根の局面で空きマスがその数以下の場合は full_search と同じ手を返す）
+///
+/// visits が NULL でない場合は、ビット番号ごとの根の子の訪問回数を長さ 64 の配列に書き込む
+///
+/// # Safety
+///
+/// visits は NULL か、書き込み可能な長さ 64 の配列を指す必要がある
+int32_t mcts_choose_move_with_solver(uint64_t myself, uint64_t opponent, uint64_t playouts, uint64_t millis, int32_t concurrency, int32_t guided, int32_t solver_empties, uint64_t seed, uint64_t *visits);
+
+/// 相手の手番の局面で先読みを始め、先読みのハンドルを返す
+///
+/// myself は自分の石、opponent は次に打つ相手の石を表す
+///
+/// predicted は予想する相手の手のビット番号で、負の値の場合は浅い探索によって予想する
+///
+/// 返されたハンドルは ponder_resolve か ponder_cancel によって必ず解放する必要がある
+Ponder *ponder_start(uint64_t myself, uint64_t opponent, int32_t predicted, int32_t concurrency);
+
+/// 設定 config に従って ponder_start と同じ先読みを始め、先読みのハンドルを返す
+///
+/// 設定は呼び出し時に複製されるので、config はこの関数から戻った後に変更や解放をしてもよい
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+Ponder *ponder_start_with_config(const EngineConfig *config, uint64_t myself, uint64_t opponent, int32_t predicted, int32_t concurrency);
+
+/// 先読みで予想した相手の手のビット番号を返す（パスの場合は -1）
+///
+/// # Safety
+///
+/// ponder は ponder_start が返した解放されていないハンドルである必要がある
+int32_t ponder_predicted_move(const Ponder *ponder);
+
+/// 実際の相手の手 actual（パスの場合は -1）を受け取り、その後の局面での自分の手のビット番号を返す
+///
+/// 予想が当たった場合は先読みの結果を使い、外れた場合は改めて探索する
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// actual が相手の打てる手でない場合や、相手が打てるのに -1 の場合は -1 を返す
+///
+/// 呼び出し後はハンドルが解放される
+///
+/// # Safety
+///
+/// ponder は ponder_start が返した解放されていないハンドルである必要がある
+int32_t ponder_resolve(Ponder *ponder, int32_t actual);
+
+/// 先読みを中断してハンドルを解放する
+///
+/// # Safety
+///
+/// ponder は ponder_start が返した解放されていないハンドルである必要がある
+void ponder_cancel(Ponder *ponder);
+
+/// choose_move_parallel_with と同じ探索をし、探索の途中経過を callback に渡しながら最良の手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// callback は呼び出したスレッドで interval_ms ミリ秒ごとと、反復して深くする部分探索で深さごとの探索を終えたときに呼ばれる
+///
+/// callback には user_data がそのまま渡される
+///
+/// # Safety
+///
+/// callback は NULL か、user_data を受け取って呼び出せる関数である必要がある
+int32_t choose_move_with_progress(uint64_t myself, uint64_t opponent, int32_t concurrency, uint32_t interval_ms, ProgressCallback callback, void *user_data);
+
+/// このライブラリのバージョンを "1.0.2" のような NUL 終端の文字列で返す
+///
+/// 返される文字列は静的な領域にあり、解放する必要はない
+const char *reversi_version(void);
+
+/// このライブラリの ABI のバージョンを返す
+///
+/// ヘッダーの REVERSI_ABI_VERSION と異なる場合は、ヘッダーとライブラリに互換性がない
+uint32_t reversi_abi_version(void);
+
+/// このライブラリが対応している機能を REVERSI_CAPABILITY_* の論理和で返す
+///
+/// ヘッダーにある機能ビットが立っていない場合は、その機能の関数を呼び出してはならない
+uint64_t reversi_capabilities(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,22 @@
+//! GTP にならった行単位のプロトコルのエンジン
+//!
+//! 標準入力からコマンドを受け取り、標準出力に応答を書き出す
+
+use reversi_ai::GtpEngine;
+use std::io::{self, BufRead, Write};
+
+fn main() -> io::Result<()> {
+    let mut engine = GtpEngine::new();
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        if let Some(response) = engine.handle(&line?) {
+            write!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+        if engine.is_quit() {
+            break;
+        }
+    }
+    Ok(())
+}
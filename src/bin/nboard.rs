@@ -0,0 +1,24 @@
+//! NBoard プロトコルのエンジン
+//!
+//! 標準入力から GUI のコマンドを受け取り、標準出力に応答を書き出す
+
+use reversi_ai::NBoardEngine;
+use std::io::{self, BufRead, Write};
+
+fn main() -> io::Result<()> {
+    let mut engine = NBoardEngine::new(num_cpus::get() as i32);
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        match engine.handle(&line?) {
+            Some(response) => {
+                for line in response {
+                    writeln!(stdout, "{line}")?;
+                }
+                stdout.flush()?;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
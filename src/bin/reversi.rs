@@ -0,0 +1,505 @@
+//! 局面の解析や対局のためのコマンドラインツール
+//!
+//! 局面は "start"（初期局面）、"f5d6c3" のような棋譜、または parse_board が読める盤面の表記で指定する
+
+use reversi_ai::{
+    FFO_FIRST_NUMBER, Game, Level, MatchConfig, MctsConfig, Sprt, SprtDecision, analyze, c_header,
+    choose_move, choose_move_random, count_bits, ffo_problems, fit_probcut, full_search,
+    full_search_parallel_stats, full_search_stats, heuristic_search, mcts_search, parse_board,
+    parse_obf, parse_square, perft, play_match, render_board, square_name,
+};
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Instant;
+
+const USAGE: &str = "\
+usage: reversi <command> [options]
+
+commands:
+  solve <position>                         exact score and best line
+  analyze <position> [--depth N]           score every move at depth N
+  play [<position>] [--white] [--level N]  play against the engine
+  perft <depth> [<position>]               count leaf nodes
+  bench                                    time a set of searches
+  ffo [<file>] [--threads N]               run the endgame suite (or an OBF file)
+  match <engine> <engine> [--games N] [--threads N] [--plies N] [--margin N] [--sprt]
+                                           play a match between two engines
+  header                                   print the C header for the shared library
+  probcut [--samples N] [--depth N] [--seed N]
+                                           fit the ProbCut parameters and print them
+
+positions are \"start\", a transcript such as \"f5d6c3\", or a board string
+of 64 squares (X, O, -) followed by the side to move
+
+engines are \"default\" (choose_move), \"level:N\", \"depth:N\" (heuristic_search),
+\"random:MARGIN\" (choose_move_random) or \"mcts:PLAYOUTS\"";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, args)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let result = match command.as_str() {
+        "solve" => solve(args),
+        "analyze" => analyze_command(args),
+        "play" => play(args),
+        "perft" => perft_command(args),
+        "bench" => bench(),
+        "ffo" => ffo(args),
+        "match" => match_command(args),
+        "probcut" => probcut(args),
+        "header" => {
+            print!("{}", c_header());
+            Ok(())
+        }
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        _ => Err(format!("unknown command: {command}")),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// 引数の局面の表記を読み込む
+fn parse_position(text: &str) -> Result<Game, String> {
+    if text == "start" {
+        return Ok(Game::new());
+    }
+    if let Some(game) = parse_board(text) {
+        return Ok(game);
+    }
+    Game::from_transcript(text).map_err(|e| format!("invalid position \"{text}\": {e}"))
+}
+
+// "--name value" の形のオプションの名前と値の組（flags に含まれる名前の値は空）
+type Options<'a> = Vec<(&'a str, &'a str)>;
+
+// 引数を位置引数とオプションに分ける
+fn split_options<'a>(
+    args: &'a [String],
+    flags: &[&str],
+) -> Result<(Vec<&'a str>, Options<'a>), String> {
+    let mut positional = Vec::new();
+    let mut options = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(name) = arg.strip_prefix("--") {
+            if flags.contains(&name) {
+                options.push((name, ""));
+            } else {
+                let value = iter.next().ok_or(format!("missing value for --{name}"))?;
+                options.push((name, value.as_str()));
+            }
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+    Ok((positional, options))
+}
+
+// 数値のオプションを読み込む
+fn parse_number<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for --{name}: {value}"))
+}
+
+// 手のビット番号の表記を返す（パスの場合は "pass"）
+fn move_name(index: i32) -> String {
+    if index < 0 {
+        "pass".to_string()
+    } else {
+        square_name(index)
+    }
+}
+
+fn solve(args: &[String]) -> Result<(), String> {
+    let [position] = args else {
+        return Err("usage: reversi solve <position>".to_string());
+    };
+    let game = parse_position(position)?;
+    let threads = num_cpus::get() as i32;
+    let black = game.black_to_move();
+    // 両者が最善を尽くした場合の手順を終局まで求め、最初の探索の値を局面の評価とする
+    let mut score = None;
+    let mut line = Vec::new();
+    let mut game = game;
+    while !game.is_over() {
+        if game.must_pass() {
+            game.pass().map_err(|e| e.to_string())?;
+            line.push(move_name(-1));
+        } else {
+            let stats = full_search_parallel_stats(game.myself(), game.opponent(), threads);
+            // パスの後の探索の値は相手から見た値なので反転する
+            score.get_or_insert(if game.black_to_move() == black {
+                stats.score
+            } else {
+                -stats.score
+            });
+            game.play(stats.best_move).map_err(|e| e.to_string())?;
+            line.push(move_name(stats.best_move));
+        }
+    }
+    let score = score.unwrap_or(game.score() * if black { 1 } else { -1 });
+    println!("score {score:+}");
+    println!("line {}", line.join(" "));
+    Ok(())
+}
+
+fn analyze_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = split_options(args, &[])?;
+    let [position] = positional[..] else {
+        return Err("usage: reversi analyze <position> [--depth N]".to_string());
+    };
+    let game = parse_position(position)?;
+    let mut depth = 7;
+    for (name, value) in options {
+        match name {
+            "depth" => depth = parse_number(name, value)?,
+            _ => return Err(format!("unknown option --{name}")),
+        }
+    }
+    let threads = num_cpus::get() as i32;
+    for score in analyze(game.myself(), game.opponent(), depth, threads) {
+        let kind = if score.exact { "exact" } else { "depth" };
+        println!("{} {:+} ({kind})", square_name(score.index), score.score);
+    }
+    Ok(())
+}
+
+fn play(args: &[String]) -> Result<(), String> {
+    let (positional, options) = split_options(args, &["white"])?;
+    let mut game = match positional[..] {
+        [] => Game::new(),
+        [position] => parse_position(position)?,
+        _ => return Err("usage: reversi play [<position>] [--white] [--level N]".to_string()),
+    };
+    let mut human_black = true;
+    let mut level = Level::Maximum;
+    for (name, value) in options {
+        match name {
+            "white" => human_black = false,
+            "level" => {
+                level = Level::from_number(parse_number(name, value)?)
+                    .ok_or(format!("level must be 1 to 10: {value}"))?
+            }
+            _ => return Err(format!("unknown option --{name}")),
+        }
+    }
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut seed = 0;
+    while !game.is_over() {
+        println!("{}", render_board(&game));
+        if game.must_pass() {
+            println!("{} passes", side_name(game.black_to_move()));
+            game.pass().map_err(|e| e.to_string())?;
+            continue;
+        }
+        if game.black_to_move() == human_black {
+            print!("{} to move (or undo, quit): ", side_name(human_black));
+            io::stdout().flush().map_err(|e| e.to_string())?;
+            let Some(line) = lines.next() else {
+                return Ok(());
+            };
+            let line = line.map_err(|e| e.to_string())?;
+            match line.trim() {
+                "quit" => return Ok(()),
+                "undo" => {
+                    // 人間が打つ直前の局面まで戻す
+                    while game.undo() && game.black_to_move() != human_black {}
+                }
+                text => match parse_square(text).map(|i| game.play(i)) {
+                    Some(Ok(())) => (),
+                    Some(Err(e)) => println!("{e}"),
+                    None => println!("enter a square such as d3"),
+                },
+            }
+        } else {
+            let index = level.choose_move(game.myself(), game.opponent(), seed);
+            seed += 1;
+            println!(
+                "{} plays {}",
+                side_name(game.black_to_move()),
+                move_name(index)
+            );
+            game.play(index).map_err(|e| e.to_string())?;
+        }
+    }
+    println!("{}", render_board(&game));
+    let score = game.score();
+    match score {
+        0 => println!("draw"),
+        _ => println!("{} wins by {}", side_name(score > 0), score.abs()),
+    }
+    Ok(())
+}
+
+// 手番の色の名前を返す
+fn side_name(black: bool) -> &'static str {
+    if black { "black" } else { "white" }
+}
+
+fn perft_command(args: &[String]) -> Result<(), String> {
+    let (depth, game) = match args {
+        [depth] => (depth, Game::new()),
+        [depth, position] => (depth, parse_position(position)?),
+        _ => return Err("usage: reversi perft <depth> [<position>]".to_string()),
+    };
+    let depth = parse_number("depth", depth)?;
+    let start = Instant::now();
+    let nodes = perft(game.myself(), game.opponent(), depth);
+    let elapsed = start.elapsed().as_secs_f64();
+    println!("perft({depth}) = {nodes} ({elapsed:.3} s)");
+    Ok(())
+}
+
+// 計測に用いる局面と探索
+const BENCH_HEURISTIC: [(&str, i32); 2] = [("f5d6c3d3c4f4f6f3e6e7", 7), ("f5f6e6f4e3c5c4", 7)];
+const BENCH_FULL: [&str; 2] = [
+    "--XXXXX--OOOXX-O-OOOXXOX-OXOXOXXOXXXOXXX--XOXOXX-XXXOOO--OOOOO-- X",
+    "-OOOOO-X-XOOOOX-XOOOOOO-XXXXXOO--XXOOXO-OOXOXXX---OXXO---OOO--O- X",
+];
+
+fn bench() -> Result<(), String> {
+    let total = Instant::now();
+    let game = Game::new();
+    let start = Instant::now();
+    let nodes = perft(game.myself(), game.opponent(), 9);
+    println!(
+        "perft 9: {nodes} nodes, {:.3} s",
+        start.elapsed().as_secs_f64()
+    );
+    for (transcript, depth) in BENCH_HEURISTIC {
+        let game = parse_position(transcript)?;
+        let start = Instant::now();
+        let index = heuristic_search(game.myself(), game.opponent(), depth);
+        println!(
+            "heuristic {transcript} depth {depth}: {}, {:.3} s",
+            move_name(index),
+            start.elapsed().as_secs_f64()
+        );
+    }
+    for board in BENCH_FULL {
+        let game = parse_position(board)?;
+        let start = Instant::now();
+        let index = full_search(game.myself(), game.opponent());
+        println!(
+            "full {}: {}, {:.3} s",
+            &board[..16],
+            move_name(index),
+            start.elapsed().as_secs_f64()
+        );
+    }
+    println!("total {:.3} s", total.elapsed().as_secs_f64());
+    Ok(())
+}
+
+fn ffo(args: &[String]) -> Result<(), String> {
+    let (positional, options) = split_options(args, &[])?;
+    let mut threads = 1;
+    for (name, value) in options {
+        match name {
+            "threads" => threads = parse_number(name, value)?,
+            _ => return Err(format!("unknown option --{name}")),
+        }
+    }
+    let (problems, first) = match positional[..] {
+        [] => (ffo_problems(), FFO_FIRST_NUMBER),
+        [path] => {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+            let problems = text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| parse_obf(line).ok_or(format!("invalid line: {line}")))
+                .collect::<Result<Vec<_>, _>>()?;
+            (problems, 1)
+        }
+        _ => return Err("usage: reversi ffo [<file>] [--threads N]".to_string()),
+    };
+    let mut total_nodes = 0;
+    let mut total_time = 0.0;
+    let mut failures = 0;
+    for (number, problem) in (first..).zip(&problems) {
+        let myself = problem.game.myself();
+        let opponent = problem.game.opponent();
+        let start = Instant::now();
+        let result = if threads > 1 {
+            full_search_parallel_stats(myself, opponent, threads)
+        } else {
+            full_search_stats(myself, opponent)
+        };
+        let elapsed = start.elapsed().as_secs_f64();
+        // 評価の記されていない局面は結果だけを示す
+        let ok = problem.score().is_none_or(|score| {
+            score == result.score && problem.best_moves().contains(&result.best_move)
+        });
+        if !ok {
+            failures += 1;
+        }
+        let expected = problem.score().map_or(String::new(), |s| format!("{s:+}"));
+        println!(
+            "#{number:<3} {:>2} empties  {:<4} {:+3} (expected {expected:>3})  {:<2}  {elapsed:8.3} s  {:>12} nodes  {:>10.0} n/s",
+            64 - count_bits(myself | opponent),
+            move_name(result.best_move),
+            result.score,
+            if ok { "ok" } else { "NG" },
+            result.nodes,
+            result.nodes as f64 / elapsed.max(1e-9),
+        );
+        total_nodes += result.nodes;
+        total_time += elapsed;
+    }
+    println!(
+        "total {total_time:.3} s  {total_nodes} nodes  {:.0} n/s",
+        total_nodes as f64 / total_time.max(1e-9)
+    );
+    if failures > 0 {
+        return Err(format!("{failures} of {} problems failed", problems.len()));
+    }
+    Ok(())
+}
+
+// 対局させるエンジン
+type BoxedEngine = Box<dyn Fn(u64, u64, u64) -> i32 + Sync>;
+
+// エンジンの指定を読み込む
+fn parse_engine(text: &str) -> Result<BoxedEngine, String> {
+    let (name, value) = text.split_once(':').unwrap_or((text, ""));
+    let number = || {
+        value
+            .parse::<i32>()
+            .map_err(|_| format!("invalid engine: {text}"))
+    };
+    Ok(match name {
+        "default" => Box::new(|myself, opponent, _| choose_move(myself, opponent)),
+        "level" => {
+            let level = Level::from_number(number()?).ok_or(format!("invalid engine: {text}"))?;
+            Box::new(move |myself, opponent, seed| level.choose_move(myself, opponent, seed))
+        }
+        "depth" => {
+            let depth = number()?;
+            Box::new(move |myself, opponent, _| heuristic_search(myself, opponent, depth))
+        }
+        "random" => {
+            let margin = number()?;
+            Box::new(move |myself, opponent, seed| {
+                choose_move_random(myself, opponent, margin, seed)
+            })
+        }
+        "mcts" => {
+            let playouts = number()? as u64;
+            Box::new(move |myself, opponent, seed| {
+                let config = MctsConfig {
+                    playouts,
+                    concurrency: 1,
+                    seed,
+                    ..MctsConfig::default()
+                };
+                mcts_search(myself, opponent, &config).best_move
+            })
+        }
+        _ => return Err(format!("unknown engine: {text}")),
+    })
+}
+
+fn match_command(args: &[String]) -> Result<(), String> {
+    let (positional, options) = split_options(args, &["sprt"])?;
+    let [first, second] = positional[..] else {
+        return Err("usage: reversi match <engine> <engine> [options]".to_string());
+    };
+    let mut config = MatchConfig::default();
+    for (name, value) in options {
+        match name {
+            "games" => config.games = parse_number(name, value)?,
+            "threads" => config.concurrency = parse_number(name, value)?,
+            "plies" => config.opening_plies = parse_number(name, value)?,
+            "margin" => config.opening_margin = parse_number(name, value)?,
+            "sprt" => config.sprt = Some(Sprt::default()),
+            _ => return Err(format!("unknown option --{name}")),
+        }
+    }
+    let engines = (parse_engine(first)?, parse_engine(second)?);
+    let start = Instant::now();
+    let result = play_match(&engines.0, &engines.1, &config);
+    println!(
+        "{first} vs {second}: +{} ={} -{} ({:.1}%), discs {}-{}, {:.1} s",
+        result.wins,
+        result.draws,
+        result.losses,
+        result.score() * 100.0,
+        result.discs,
+        result.opponent_discs,
+        start.elapsed().as_secs_f64()
+    );
+    match result.elo() {
+        Some((elo, error)) => println!("elo {elo:+.1} +/- {error:.1}"),
+        None => println!("elo not available"),
+    }
+    if let Some(sprt) = &config.sprt {
+        let decision = match result.sprt {
+            Some(SprtDecision::H0) => "H0 accepted",
+            Some(SprtDecision::H1) => "H1 accepted",
+            None => "inconclusive",
+        };
+        println!(
+            "sprt [{}, {}] llr {:.2}: {decision}",
+            sprt.elo0,
+            sprt.elo1,
+            result.llr(sprt)
+        );
+    }
+    Ok(())
+}
+
+fn probcut(args: &[String]) -> Result<(), String> {
+    let (positional, options) = split_options(args, &[])?;
+    if !positional.is_empty() {
+        return Err("usage: reversi probcut [--samples N] [--depth N] [--seed N]".to_string());
+    }
+    let mut samples = 200;
+    let mut depth = 9;
+    let mut seed = 0;
+    for (name, value) in options {
+        match name {
+            "samples" => samples = parse_number(name, value)?,
+            "depth" => depth = parse_number(name, value)?,
+            "seed" => seed = parse_number(name, value)?,
+            _ => return Err(format!("unknown option --{name}")),
+        }
+    }
+    if samples < 2 || depth < 3 {
+        return Err("--samples must be at least 2 and --depth at least 3".to_string());
+    }
+    let start = Instant::now();
+    let parameters = fit_probcut(samples as usize, depth, seed as u64);
+    // src/probcut.rs の PARAMETERS にそのまま貼り付けられる形で出力する
+    println!(
+        "// {samples} samples, seed {seed}, {:.1} s",
+        start.elapsed().as_secs_f64()
+    );
+    println!(
+        "const PARAMETERS: [ProbCutParameter; {}] = [",
+        parameters.len()
+    );
+    for p in parameters {
+        println!("    ProbCutParameter {{");
+        println!("        depth: {},", p.depth);
+        println!("        shallow_depth: {},", p.shallow_depth);
+        println!("        slope: {:.4},", p.slope);
+        println!("        intercept: {:.1},", p.intercept);
+        println!("        sigma: {:.1},", p.sigma);
+        println!("    }},");
+    }
+    println!("];");
+    Ok(())
+}
@@ -0,0 +1,505 @@
+use crate::level::Level;
+use crate::strategy::REVERSI_STRATEGY_FULL_WINDOW;
+use crate::{
+    MoveScore, NEVER_STOP, analyze_sub, choose_move_parallel_sub, count_bits, full_search,
+    heuristic_search_parallel_sub, heuristic_search_random_sub, heuristic_search_root,
+    level::choose_level_move, openness, possible_moves, sum_of_weights,
+};
+use std::cmp::max;
+use std::ffi::{CStr, c_char};
+
+/// 探索の振る舞いを決める設定
+///
+/// 既定値 EngineConfig::DEFAULT は、設定を受け取らない関数の振る舞いと等しい
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EngineConfig {
+    /// 逐次版の探索が完全探索に切り替える石の数（盤上の石がこれより多い局面で完全探索する）
+    pub full_search_occupancy: i32,
+    /// 並列版の探索が完全探索に切り替える石の数
+    pub parallel_full_search_occupancy: i32,
+    /// 先読みの深さを切り替える打てる手の数（両者の打てる手の数の多い方がこれより多い局面では浅く読む）
+    pub branching_threshold: i32,
+    /// 逐次版の探索で打てる手が多い局面の先読みの深さ
+    pub wide_depth: i32,
+    /// 逐次版の探索で打てる手が少ない局面の先読みの深さ
+    pub narrow_depth: i32,
+    /// 並列版の探索で打てる手が多い局面の先読みの深さ
+    pub parallel_wide_depth: i32,
+    /// 並列版の探索で打てる手が少ない局面の先読みの深さ
+    pub parallel_narrow_depth: i32,
+    /// 部分探索で勝敗が決まった局面の評価値の絶対値
+    pub confident_victory: i32,
+    /// 静的評価関数で打てる手の数の差に掛ける、盤上の石1個あたりの係数
+    pub mobility_weight: i32,
+    /// 開放度に掛ける係数
+    pub openness_weight: i32,
+    /// 部分探索で ProbCut による枝刈りをする閾値（浅い探索から予測した値の標準偏差の百分率、0 以下なら枝刈りしない）
+    pub probcut_threshold: i32,
+    /// 部分探索の根の探索の戦略（REVERSI_STRATEGY_* のいずれか）
+    pub root_strategy: i32,
+    /// REVERSI_STRATEGY_ASPIRATION で最初に用いる窓の予想した評価値からの幅（REVERSI_STRATEGY_MTDF では限界から離して調べる最初の幅）
+    pub aspiration_window: i32,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig::DEFAULT
+    }
+}
+
+impl EngineConfig {
+    /// 既定の設定
+    pub const DEFAULT: EngineConfig = EngineConfig {
+        full_search_occupancy: 50,
+        parallel_full_search_occupancy: 48,
+        branching_threshold: 8,
+        wide_depth: 5,
+        narrow_depth: 7,
+        parallel_wide_depth: 7,
+        parallel_narrow_depth: 9,
+        confident_victory: 100000000,
+        mobility_weight: 50,
+        openness_weight: 10,
+        probcut_threshold: 0,
+        root_strategy: REVERSI_STRATEGY_FULL_WINDOW,
+        aspiration_window: 3000,
+    };
+
+    /// get と set で用いる設定項目の名前
+    pub const OPTION_NAMES: [&'static str; 13] = [
+        "full_search_occupancy",
+        "parallel_full_search_occupancy",
+        "branching_threshold",
+        "wide_depth",
+        "narrow_depth",
+        "parallel_wide_depth",
+        "parallel_narrow_depth",
+        "confident_victory",
+        "mobility_weight",
+        "openness_weight",
+        "probcut_threshold",
+        "root_strategy",
+        "aspiration_window",
+    ];
+
+    // 名前に対応する設定項目への参照を返す
+    fn option_mut(&mut self, name: &str) -> Option<&mut i32> {
+        Some(match name {
+            "full_search_occupancy" => &mut self.full_search_occupancy,
+            "parallel_full_search_occupancy" => &mut self.parallel_full_search_occupancy,
+            "branching_threshold" => &mut self.branching_threshold,
+            "wide_depth" => &mut self.wide_depth,
+            "narrow_depth" => &mut self.narrow_depth,
+            "parallel_wide_depth" => &mut self.parallel_wide_depth,
+            "parallel_narrow_depth" => &mut self.parallel_narrow_depth,
+            "confident_victory" => &mut self.confident_victory,
+            "mobility_weight" => &mut self.mobility_weight,
+            "openness_weight" => &mut self.openness_weight,
+            "probcut_threshold" => &mut self.probcut_threshold,
+            "root_strategy" => &mut self.root_strategy,
+            "aspiration_window" => &mut self.aspiration_window,
+            _ => return None,
+        })
+    }
+
+    /// 名前が name の設定項目の値を返す（名前が不明な場合は None を返す）
+    pub fn get(&self, name: &str) -> Option<i32> {
+        let mut config = *self;
+        config.option_mut(name).map(|v| *v)
+    }
+
+    /// 名前が name の設定項目を value にし、設定できたかを返す
+    pub fn set(&mut self, name: &str, value: i32) -> bool {
+        match self.option_mut(name) {
+            Some(v) => {
+                *v = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// この設定での静的評価関数の値を返す（myself プレイヤーに有利なほど大きい）
+    pub fn evaluation(&self, myself: u64, opponent: u64) -> i32 {
+        let k = self.mobility_weight * count_bits(myself | opponent);
+        (sum_of_weights(myself) - sum_of_weights(opponent))
+            + k * (count_bits(possible_moves(myself, opponent))
+                - count_bits(possible_moves(opponent, myself)))
+    }
+
+    /// この設定の係数を掛けた turns 周りの開放度の負値を返す
+    pub fn openness_evaluation(&self, myself: u64, opponent: u64, turns: u64) -> i32 {
+        -self.openness_weight * openness(myself, opponent, turns)
+    }
+
+    // 逐次版の探索が部分探索で用いる先読みの深さを返す（完全探索をする局面では None を返す）
+    pub(crate) fn search_depth(&self, myself: u64, opponent: u64) -> Option<i32> {
+        self.depth_for(
+            myself,
+            opponent,
+            self.full_search_occupancy,
+            self.wide_depth,
+            self.narrow_depth,
+        )
+    }
+
+    // 並列版の探索が部分探索で用いる先読みの深さを返す（完全探索をする局面では None を返す）
+    pub(crate) fn parallel_search_depth(&self, myself: u64, opponent: u64) -> Option<i32> {
+        self.depth_for(
+            myself,
+            opponent,
+            self.parallel_full_search_occupancy,
+            self.parallel_wide_depth,
+            self.parallel_narrow_depth,
+        )
+    }
+
+    fn depth_for(
+        &self,
+        myself: u64,
+        opponent: u64,
+        occupancy: i32,
+        wide_depth: i32,
+        narrow_depth: i32,
+    ) -> Option<i32> {
+        if count_bits(myself | opponent) > occupancy {
+            return None;
+        }
+        let move_count = max(
+            count_bits(possible_moves(myself, opponent)),
+            count_bits(possible_moves(opponent, myself)),
+        );
+        Some(if move_count > self.branching_threshold {
+            wide_depth
+        } else {
+            narrow_depth
+        })
+    }
+
+    /// heuristic_search と同じ部分探索をこの設定で行う
+    pub fn heuristic_search(&self, myself: u64, opponent: u64, depth: i32) -> i32 {
+        heuristic_search_root(self, myself, opponent, depth)
+    }
+
+    /// heuristic_search_parallel_with と同じ部分探索をこの設定で行う
+    pub fn heuristic_search_parallel(
+        &self,
+        myself: u64,
+        opponent: u64,
+        depth: i32,
+        concurrency: i32,
+    ) -> i32 {
+        heuristic_search_parallel_sub(self, myself, opponent, depth, concurrency, &NEVER_STOP)
+    }
+
+    /// heuristic_search_random と同じ部分探索をこの設定で行う
+    pub fn heuristic_search_random(
+        &self,
+        myself: u64,
+        opponent: u64,
+        depth: i32,
+        margin: i32,
+        seed: u64,
+    ) -> i32 {
+        heuristic_search_random_sub(self, myself, opponent, depth, margin, seed)
+    }
+
+    /// choose_move と同じ探索をこの設定で行う
+    pub fn choose_move(&self, myself: u64, opponent: u64) -> i32 {
+        match self.search_depth(myself, opponent) {
+            None => full_search(myself, opponent),
+            Some(depth) => self.heuristic_search(myself, opponent, depth),
+        }
+    }
+
+    /// choose_move_parallel_with と同じ探索をこの設定で行う
+    pub fn choose_move_parallel(&self, myself: u64, opponent: u64, concurrency: i32) -> i32 {
+        choose_move_parallel_sub(self, myself, opponent, concurrency, &NEVER_STOP)
+    }
+
+    /// choose_move_random と同じ探索をこの設定で行う
+    pub fn choose_move_random(&self, myself: u64, opponent: u64, margin: i32, seed: u64) -> i32 {
+        match self.search_depth(myself, opponent) {
+            None => full_search(myself, opponent),
+            Some(depth) => self.heuristic_search_random(myself, opponent, depth, margin, seed),
+        }
+    }
+
+    /// Level::choose_move と同じ探索をこの設定の評価関数で行う
+    pub fn choose_move_level(&self, level: Level, myself: u64, opponent: u64, seed: u64) -> i32 {
+        choose_level_move(self, level, myself, opponent, seed)
+    }
+
+    /// analyze と同じ解析をこの設定で行う
+    pub fn analyze(
+        &self,
+        myself: u64,
+        opponent: u64,
+        depth: i32,
+        concurrency: i32,
+    ) -> Vec<MoveScore> {
+        analyze_sub(self, myself, opponent, depth, concurrency, &NEVER_STOP)
+    }
+}
+
+/// 既定値の設定を作り、そのハンドルを返す
+///
+/// 返されたハンドルは engine_config_free によって解放する必要がある
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_config_new() -> *mut EngineConfig {
+    Box::into_raw(Box::new(EngineConfig::DEFAULT))
+}
+
+/// 設定のハンドルを解放する
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_config_free(config: *mut EngineConfig) {
+    drop(unsafe { Box::from_raw(config) });
+}
+
+/// 名前が name の設定項目を value にし、設定できた場合は 1 を、名前が不明な場合は 0 を返す
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルで、name は NUL 終端の文字列である必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_config_set(
+    config: *mut EngineConfig,
+    name: *const c_char,
+    value: i32,
+) -> i32 {
+    let name = unsafe { CStr::from_ptr(name) };
+    let config = unsafe { &mut *config };
+    name.to_str().is_ok_and(|name| config.set(name, value)) as i32
+}
+
+/// 名前が name の設定項目の値を value に書き込み、書き込めた場合は 1 を、名前が不明な場合は 0 を返す
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドル、name は NUL 終端の文字列、value は書き込み可能な領域を指す必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_config_get(
+    config: *const EngineConfig,
+    name: *const c_char,
+    value: *mut i32,
+) -> i32 {
+    let name = unsafe { CStr::from_ptr(name) };
+    let config = unsafe { &*config };
+    match name.to_str().ok().and_then(|name| config.get(name)) {
+        Some(v) => {
+            unsafe { *value = v };
+            1
+        }
+        None => 0,
+    }
+}
+
+/// 設定 config に従って choose_move と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn choose_move_with_config(
+    config: *const EngineConfig,
+    myself: u64,
+    opponent: u64,
+) -> i32 {
+    unsafe { &*config }.choose_move(myself, opponent)
+}
+
+/// 設定 config に従って choose_move_parallel_with と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn choose_move_parallel_with_config(
+    config: *const EngineConfig,
+    myself: u64,
+    opponent: u64,
+    concurrency: i32,
+) -> i32 {
+    unsafe { &*config }.choose_move_parallel(myself, opponent, concurrency)
+}
+
+/// 設定 config に従って choose_move_random と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn choose_move_random_with_config(
+    config: *const EngineConfig,
+    myself: u64,
+    opponent: u64,
+    margin: i32,
+    seed: u64,
+) -> i32 {
+    unsafe { &*config }.choose_move_random(myself, opponent, margin, seed)
+}
+
+/// 設定 config に従って choose_move_level と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn choose_move_level_with_config(
+    config: *const EngineConfig,
+    myself: u64,
+    opponent: u64,
+    level: i32,
+    seed: u64,
+) -> i32 {
+    let level = Level::from_number(level.clamp(1, 10)).unwrap();
+    unsafe { &*config }.choose_move_level(level, myself, opponent, seed)
+}
+
+/// 設定 config に従って heuristic_search と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn heuristic_search_with_config(
+    config: *const EngineConfig,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+) -> i32 {
+    unsafe { &*config }.heuristic_search(myself, opponent, depth)
+}
+
+/// 設定 config に従って heuristic_search_parallel_with と同じ探索をする
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn heuristic_search_parallel_with_config(
+    config: *const EngineConfig,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    concurrency: i32,
+) -> i32 {
+    unsafe { &*config }.heuristic_search_parallel(myself, opponent, depth, concurrency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_position;
+    use crate::{choose_move, choose_move_parallel_with, evaluation, heuristic_search};
+    use std::ffi::CString;
+
+    // 序盤の局面
+    const MIDGAME: &str = "
+        --X--O--
+        ---XOO--
+        --OOXOO-
+        -OOOOOO-
+        ---OOO--
+        ----O---
+        --------
+        -------- X";
+
+    #[test]
+    fn option_test() {
+        let mut config = EngineConfig::default();
+        for name in EngineConfig::OPTION_NAMES {
+            let value = config.get(name).unwrap();
+            assert!(config.set(name, value + 1));
+            assert_eq!(Some(value + 1), config.get(name));
+        }
+        assert_ne!(EngineConfig::DEFAULT, config);
+        assert_eq!(None, config.get("unknown"));
+        assert!(!config.set("unknown", 0));
+    }
+
+    #[test]
+    fn default_config_test() {
+        // 既定値は設定を受け取らない関数と同じ結果になる
+        let config = EngineConfig::DEFAULT;
+        let (myself, opponent) = parse_position(MIDGAME);
+        assert_eq!(
+            evaluation(myself, opponent),
+            config.evaluation(myself, opponent)
+        );
+        assert_eq!(
+            choose_move(myself, opponent),
+            config.choose_move(myself, opponent)
+        );
+        assert_eq!(
+            choose_move_parallel_with(myself, opponent, 2),
+            config.choose_move_parallel(myself, opponent, 2)
+        );
+        assert_eq!(
+            heuristic_search(myself, opponent, 5),
+            config.heuristic_search(myself, opponent, 5)
+        );
+    }
+
+    #[test]
+    fn custom_config_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        // 打てる手の数を重視すると評価値が変わる
+        let config = EngineConfig {
+            mobility_weight: 1000,
+            ..EngineConfig::DEFAULT
+        };
+        assert_ne!(
+            evaluation(myself, opponent),
+            config.evaluation(myself, opponent)
+        );
+        // 先読みの深さは設定に従う
+        let config = EngineConfig {
+            wide_depth: 1,
+            narrow_depth: 1,
+            ..EngineConfig::DEFAULT
+        };
+        assert_eq!(
+            config.heuristic_search(myself, opponent, 1),
+            config.choose_move(myself, opponent)
+        );
+        // 完全探索に切り替える石の数は設定に従う
+        let config = EngineConfig {
+            full_search_occupancy: 0,
+            parallel_full_search_occupancy: 0,
+            ..EngineConfig::DEFAULT
+        };
+        assert_eq!(None, config.search_depth(myself, opponent));
+        assert_eq!(None, config.parallel_search_depth(myself, opponent));
+    }
+
+    #[test]
+    fn config_handle_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let config = engine_config_new();
+        let name = CString::new("wide_depth").unwrap();
+        let unknown = CString::new("unknown").unwrap();
+        let mut value = 0;
+        unsafe {
+            assert_eq!(1, engine_config_get(config, name.as_ptr(), &mut value));
+            assert_eq!(5, value);
+            assert_eq!(1, engine_config_set(config, name.as_ptr(), 3));
+            assert_eq!(1, engine_config_set(config, c"narrow_depth".as_ptr(), 3));
+            assert_eq!(0, engine_config_set(config, unknown.as_ptr(), 3));
+            assert_eq!(0, engine_config_get(config, unknown.as_ptr(), &mut value));
+            assert_eq!(
+                heuristic_search(myself, opponent, 3),
+                choose_move_with_config(config, myself, opponent)
+            );
+            assert_eq!(
+                heuristic_search(myself, opponent, 3),
+                heuristic_search_with_config(config, myself, opponent, 3)
+            );
+            engine_config_free(config);
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{SearchContext, balance, count_bits, empty_squares, index_to_bit, turnovers};
+
+// 空きマスがこの数以下になったら専用の完全探索に切り替える
+pub(crate) const LAST_EMPTIES: i32 = 4;
+
+// 空きマスがこの数以上の局面では完全探索で置換表を使う
+pub(crate) const TABLE_EMPTIES: i32 = 7;
+
+// 空きマスがこの数以上の局面では完全探索で手を並べ替える
+pub(crate) const ORDER_EMPTIES: i32 = 7;
+
+// 方向ごと（横、縦、2つの斜め）の盤上の直線のビットボード表現
+const LINES: [[u64; 15]; 4] = {
+    let mut lines = [[0; 15]; 4];
+    let mut index = 0;
+    while index < 64 {
+        let (i, j) = (index % 8, index / 8);
+        let bit = 1 << index;
+        lines[0][j] |= bit;
+        lines[1][i] |= bit;
+        lines[2][i + 7 - j] |= bit;
+        lines[3][i + j] |= bit;
+        index += 1;
+    }
+    lines
+};
+
+// 左右の端の列のビットボード表現
+const FILE_EDGES: u64 = 0x8181_8181_8181_8181;
+// 上下の端の行のビットボード表現
+const RANK_EDGES: u64 = 0xFF00_0000_0000_00FF;
+// 盤の端のビットボード表現
+const EDGES: u64 = FILE_EDGES | RANK_EDGES;
+
+// 4つの象限のビットボード表現
+const QUADRANTS: [u64; 4] = [
+    0x0000_0000_0F0F_0F0F,
+    0x0000_0000_F0F0_F0F0,
+    0x0F0F_0F0F_0000_0000,
+    0xF0F0_F0F0_0000_0000,
+];
+
+// 空きマスが LAST_EMPTIES 以下の局面を完全探索する
+//
+// 指し手の列挙をせず、空きマスごとに返せる石を直接数える
+//
+// 空きマスの数が奇数の象限にある空きマスから順に調べる
+pub(crate) fn solve_last(
+    ctx: &mut SearchContext,
+    myself: u64,
+    opponent: u64,
+    alpha: i32,
+    beta: i32,
+) -> i32 {
+    let mut squares = [0; LAST_EMPTIES as usize];
+    let mut len = 0;
+    for odd in [true, false] {
+        for quadrant in QUADRANTS {
+            let mut e = empty_squares(myself, opponent) & quadrant;
+            if (count_bits(e) % 2 == 1) != odd {
+                continue;
+            }
+            while e != 0 {
+                squares[len] = e.trailing_zeros() as i32;
+                len += 1;
+                e &= e - 1;
+            }
+        }
+    }
+    solve(ctx, myself, opponent, alpha, beta, &squares[..len])
+}
+
+// 空きマスが squares だけの局面を、squares の順に調べて完全探索する
+fn solve(
+    ctx: &mut SearchContext,
+    myself: u64,
+    opponent: u64,
+    alpha: i32,
+    beta: i32,
+    squares: &[i32],
+) -> i32 {
+    ctx.count_node();
+    match squares {
+        [] => balance(myself, opponent),
+        &[index] => solve1(myself, opponent, index),
+        _ => {
+            let mut alpha = alpha;
+            let mut moved = false;
+            for (k, &index) in squares.iter().enumerate() {
+                let turns = turnovers(myself, opponent, index);
+                if turns == 0 {
+                    continue;
+                }
+                moved = true;
+                let (rest, len) = remove(squares, k);
+                let v = -solve(
+                    ctx,
+                    opponent & !turns,
+                    myself | turns | index_to_bit(index),
+                    -beta,
+                    -alpha,
+                    &rest[..len],
+                );
+                if v > alpha {
+                    alpha = v;
+                    if alpha >= beta {
+                        break;
+                    }
+                }
+            }
+            if moved {
+                alpha
+            } else if squares
+                .iter()
+                .any(|&index| turnovers(opponent, myself, index) != 0)
+            {
+                -solve(ctx, opponent, myself, -beta, -alpha, squares)
+            } else {
+                balance(myself, opponent)
+            }
+        }
+    }
+}
+
+// 空きマスが index だけの局面の終局時の石数の差を、返せる石の数から直接求める
+fn solve1(myself: u64, opponent: u64, index: i32) -> i32 {
+    // 空きマスが1つなので、相手の石の数は 63 - n
+    let n = count_bits(myself);
+    let flips = count_bits(turnovers(myself, opponent, index));
+    if flips != 0 {
+        return 2 * (n + flips) - 62;
+    }
+    let flips = count_bits(turnovers(opponent, myself, index));
+    if flips != 0 {
+        return 2 * (n - flips) - 64;
+    }
+    2 * n - 63
+}
+
+// player プレイヤーの石のうち、この先どのように打たれても返されない確定石のビットボード表現を返す
+//
+// 4つの方向のそれぞれについて、直線が埋まっているか、両隣の一方が盤の外か同じプレイヤーの確定石である石を確定石とする
+pub(crate) fn stable_discs(player: u64, other: u64) -> u64 {
+    let occupied = player | other;
+    let [horizontal, vertical, diagonal9, diagonal7] = LINES.map(|lines| {
+        lines
+            .iter()
+            .filter(|&&line| occupied & line == line)
+            .fold(0, |full, &line| full | line)
+    });
+    // 端の列を越えてずれたビットは端の列に入るので、そのまま論理和をとってよい
+    let mut stable = 0;
+    loop {
+        let next = player
+            & (horizontal | FILE_EDGES | stable << 1 | stable >> 1)
+            & (vertical | RANK_EDGES | stable << 8 | stable >> 8)
+            & (diagonal9 | EDGES | stable << 9 | stable >> 9)
+            & (diagonal7 | EDGES | stable << 7 | stable >> 7);
+        if next == stable {
+            return stable;
+        }
+        stable = next;
+    }
+}
+
+// squares から k 番目の要素を取り除いた配列とその長さを返す
+fn remove(squares: &[i32], k: usize) -> ([i32; LAST_EMPTIES as usize], usize) {
+    let mut rest = [0; LAST_EMPTIES as usize];
+    let mut len = 0;
+    for (j, &index) in squares.iter().enumerate() {
+        if j != k {
+            rest[len] = index;
+            len += 1;
+        }
+    }
+    (rest, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use crate::{EngineConfig, INTMAX, INTMIN, NEVER_STOP, full_search_sub, place, possible_moves};
+
+    // 汎用の着手生成による完全探索
+    fn generic_search(myself: u64, opponent: u64) -> i32 {
+        let moves = possible_moves(myself, opponent);
+        if moves == 0 {
+            return if possible_moves(opponent, myself) != 0 {
+                -generic_search(opponent, myself)
+            } else {
+                balance(myself, opponent)
+            };
+        }
+        let mut best = INTMIN;
+        let mut m = moves;
+        while m != 0 {
+            let mut s = 0;
+            let mut o = 0;
+            place(myself, opponent, m.trailing_zeros() as i32, &mut s, &mut o);
+            best = best.max(-generic_search(o, s));
+            m &= m - 1;
+        }
+        best
+    }
+
+    // 初期局面から空きマスが empties になるまで乱数で打った局面を返す
+    fn random_position(random: &mut Random, empties: i32) -> (u64, u64) {
+        let mut myself: u64 = 0x0000_0008_1000_0000;
+        let mut opponent: u64 = 0x0000_0010_0800_0000;
+        while count_bits(empty_squares(myself, opponent)) > empties {
+            let moves = possible_moves(myself, opponent);
+            if moves == 0 {
+                if possible_moves(opponent, myself) == 0 {
+                    // 途中で終局した場合はやり直す
+                    return random_position(random, empties);
+                }
+            } else {
+                let mut m = moves;
+                for _ in 0..random.below(count_bits(moves) as u64) {
+                    m &= m - 1;
+                }
+                let mut s = 0;
+                let mut o = 0;
+                place(myself, opponent, m.trailing_zeros() as i32, &mut s, &mut o);
+                (myself, opponent) = (s, o);
+            }
+            (myself, opponent) = (opponent, myself);
+        }
+        (myself, opponent)
+    }
+
+    #[test]
+    fn last_empties_test() {
+        let mut random = Random::new(45);
+        for empties in 0..=LAST_EMPTIES {
+            for _ in 0..500 {
+                let (myself, opponent) = random_position(&mut random, empties);
+                let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, &NEVER_STOP);
+                let score = generic_search(myself, opponent);
+                assert_eq!(
+                    score,
+                    solve_last(&mut ctx, myself, opponent, INTMIN, INTMAX)
+                );
+                // 窓の外の値は、外れた向きの限界になる
+                for (alpha, beta) in [(score, score + 2), (score - 2, score), (-1, 1)] {
+                    let v = solve_last(&mut ctx, myself, opponent, alpha, beta);
+                    if v <= alpha {
+                        assert!(score <= v);
+                    } else if v >= beta {
+                        assert!(score >= v);
+                    } else {
+                        assert_eq!(score, v);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stable_discs_test() {
+        // 隅の石と、隅から辺に連なる石は確定石
+        assert_eq!(0x01, stable_discs(0x01, 0x02));
+        assert_eq!(0x07, stable_discs(0x07, 0x0800));
+        assert_eq!(0, stable_discs(0x0200, 0x01));
+        // 盤が埋まっていればすべての石が確定石
+        assert_eq!(
+            0x1234_5678_9ABC_DEF0,
+            stable_discs(0x1234_5678_9ABC_DEF0, !0x1234_5678_9ABC_DEF0)
+        );
+        // 確定石は乱数で打ち進めても返されない
+        let mut random = Random::new(47);
+        for _ in 0..200 {
+            let empties = random.below(40) as i32 + 1;
+            let (mut myself, mut opponent) = random_position(&mut random, empties);
+            let stable = stable_discs(myself, opponent);
+            // 最初に手番だったプレイヤーが手番かどうか
+            let mut first = true;
+            loop {
+                let moves = possible_moves(myself, opponent);
+                if moves == 0 {
+                    if possible_moves(opponent, myself) == 0 {
+                        break;
+                    }
+                } else {
+                    let mut m = moves;
+                    for _ in 0..random.below(count_bits(moves) as u64) {
+                        m &= m - 1;
+                    }
+                    let mut s = 0;
+                    let mut o = 0;
+                    place(myself, opponent, m.trailing_zeros() as i32, &mut s, &mut o);
+                    (myself, opponent) = (s, o);
+                }
+                (myself, opponent) = (opponent, myself);
+                first = !first;
+                let player = if first { myself } else { opponent };
+                assert_eq!(stable, player & stable);
+            }
+        }
+    }
+
+    #[test]
+    fn full_search_hook_test() {
+        // 専用の完全探索に切り替わる深さを含めて、汎用の完全探索と一致する
+        let mut random = Random::new(46);
+        for empties in [LAST_EMPTIES + 1, 8] {
+            for _ in 0..50 {
+                let (myself, opponent) = random_position(&mut random, empties);
+                let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, &NEVER_STOP);
+                assert_eq!(
+                    generic_search(myself, opponent),
+                    full_search_sub(&mut ctx, myself, opponent, INTMIN, INTMAX)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,513 @@
+use crate::progress::{ProgressCallback, SearchProgress, c_report, search_with_progress};
+use crate::{
+    EngineConfig, INTMIN, MoveScore, SearchShared, SearchTables, StopSignal, analyze_sub,
+    choose_move_parallel_sub,
+};
+use std::ffi::{CStr, c_char, c_void};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::time::Duration;
+
+/// 探索の設定と並列数を対局中の探索の間で持ち続ける探索エンジン
+///
+/// 完全探索の置換表と部分探索の手の順序付けの表も探索の間で持ち続け、続く局面の探索に使い回す（中断された探索の結果は表に記録しない）
+///
+/// すべての操作は &self で行え、複数のスレッドから同時に呼び出してよい
+///
+/// 同じエンジンでの探索は1つずつ順に行われ、stop によって実行中の探索や順番を待っている探索を中断できる
+pub struct SearchEngine {
+    // 探索の設定
+    config: Mutex<EngineConfig>,
+    // 探索の並列数
+    concurrency: AtomicI32,
+    // 実行中の探索の中断要求
+    stop: StopSignal,
+    // 探索の呼び出しと中断要求の受け付けの状態
+    control: Mutex<Control>,
+    // 探索を1つずつ行うためのロックと、その中で探索の間で使い回す表
+    search: Mutex<SearchTables>,
+}
+
+// 探索の呼び出しに順に振る番号で、中断要求の対象を管理する
+#[derive(Default)]
+struct Control {
+    // これまでに呼び出された探索に振った最後の番号
+    entered: u64,
+    // この番号以下の探索は中断が要求されている
+    stopped: u64,
+    // 実行中の探索の番号（なければ 0）
+    running: u64,
+}
+
+impl Default for SearchEngine {
+    fn default() -> Self {
+        SearchEngine::new()
+    }
+}
+
+impl SearchEngine {
+    /// 既定の設定と CPU スレッド数の並列数を持つエンジンを作る
+    pub fn new() -> SearchEngine {
+        SearchEngine::with_config(EngineConfig::DEFAULT, num_cpus::get() as i32)
+    }
+
+    /// 設定 config と並列数 concurrency を持つエンジンを作る
+    pub fn with_config(config: EngineConfig, concurrency: i32) -> SearchEngine {
+        SearchEngine {
+            config: Mutex::new(config),
+            concurrency: AtomicI32::new(concurrency),
+            stop: StopSignal::new(),
+            control: Mutex::new(Control::default()),
+            search: Mutex::new(SearchTables::new()),
+        }
+    }
+
+    /// 現在の設定を返す
+    pub fn config(&self) -> EngineConfig {
+        *self.config.lock().unwrap()
+    }
+
+    /// 設定を config にする（実行中の探索には影響しない）
+    pub fn set_config(&self, config: EngineConfig) {
+        *self.config.lock().unwrap() = config;
+    }
+
+    /// 探索の並列数を返す
+    pub fn concurrency(&self) -> i32 {
+        self.concurrency.load(Ordering::Relaxed)
+    }
+
+    /// 名前が name の設定項目の値を返す（名前が不明な場合は None を返す）
+    ///
+    /// EngineConfig の設定項目に加えて、"concurrency" で探索の並列数を扱える
+    pub fn option(&self, name: &str) -> Option<i32> {
+        match name {
+            "concurrency" => Some(self.concurrency()),
+            _ => self.config().get(name),
+        }
+    }
+
+    /// 名前が name の設定項目を value にし、設定できたかを返す（実行中の探索には影響しない）
+    ///
+    /// "concurrency" には 1 以上の値だけを設定できる
+    pub fn set_option(&self, name: &str, value: i32) -> bool {
+        match name {
+            "concurrency" if value >= 1 => {
+                self.concurrency.store(value, Ordering::Relaxed);
+                true
+            }
+            "concurrency" => false,
+            _ => self.config.lock().unwrap().set(name, value),
+        }
+    }
+
+    /// 実行中の探索と、既に呼び出されて順番を待っている探索の中断を要求する
+    ///
+    /// 探索中でない場合は何もせず、この後に呼び出される探索には影響しない
+    pub fn stop(&self) {
+        let mut control = self.control.lock().unwrap();
+        control.stopped = control.entered;
+        if control.running != 0 {
+            self.stop.request();
+        }
+    }
+
+    // 他の探索の終了を待ってから、中断要求と使い回す表を受け付ける探索 search を行う
+    //
+    // 順番を待っている間に stop が呼ばれた場合や、探索が stop による中断要求に気づいて打ち切った場合は None を返す
+    fn run<T>(&self, search: impl FnOnce(&EngineConfig, i32, &SearchShared) -> T) -> Option<T> {
+        let ticket = {
+            let mut control = self.control.lock().unwrap();
+            control.entered += 1;
+            control.entered
+        };
+        let tables = self.search.lock().unwrap();
+        {
+            let mut control = self.control.lock().unwrap();
+            // 順番を待っている間に中断が要求されていれば探索しない
+            if control.stopped >= ticket {
+                return None;
+            }
+            control.running = ticket;
+            self.stop.reset();
+        }
+        let config = self.config();
+        let shared = SearchShared {
+            stop: &self.stop,
+            tables: Some(&tables),
+        };
+        let result = search(&config, self.concurrency(), &shared);
+        // 探索が戻った後に届いた中断要求は、探索の結果に影響していないので無視する
+        let mut control = self.control.lock().unwrap();
+        control.running = 0;
+        if self.stop.reset() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    /// 現在の設定で choose_move_parallel_with と同じ探索をし、手のビット番号を返す
+    ///
+    /// 打つ手がない場合は -1 を、探索が中断された場合は None を返す
+    pub fn choose_move(&self, myself: u64, opponent: u64) -> Option<i32> {
+        self.run(|config, concurrency, shared| {
+            choose_move_parallel_sub(config, myself, opponent, concurrency, shared)
+        })
+    }
+
+    /// 現在の設定で EngineConfig::choose_move_with_progress と同じ探索をし、最後の途中経過を返す
+    ///
+    /// 探索が中断された場合は None を返す
+    pub fn choose_move_with_progress(
+        &self,
+        myself: u64,
+        opponent: u64,
+        interval: Duration,
+        mut report: impl FnMut(&SearchProgress),
+    ) -> Option<SearchProgress> {
+        self.run(|config, concurrency, shared| {
+            search_with_progress(
+                config,
+                myself,
+                opponent,
+                concurrency,
+                interval,
+                shared,
+                &mut report,
+            )
+        })
+    }
+
+    /// 現在の設定で analyze と同じ解析をする
+    ///
+    /// 探索が中断された場合は None を返す
+    pub fn analyze(&self, myself: u64, opponent: u64, depth: i32) -> Option<Vec<MoveScore>> {
+        self.run(|config, concurrency, shared| {
+            analyze_sub(config, myself, opponent, depth, concurrency, shared)
+        })
+    }
+}
+
+/// 既定の設定と CPU スレッド数の並列数を持つエンジンを作り、そのハンドルを返す
+///
+/// 返されたハンドルは engine_free によって解放する必要がある
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_new() -> *mut SearchEngine {
+    Box::into_raw(Box::new(SearchEngine::new()))
+}
+
+/// エンジンのハンドルを解放する
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルで、他のスレッドで使われていない必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_free(engine: *mut SearchEngine) {
+    drop(unsafe { Box::from_raw(engine) });
+}
+
+/// 名前が name の設定項目を value にし、設定できた場合は 1 を、できなかった場合は 0 を返す
+///
+/// 設定項目の名前は engine_config_set と同じものに加えて、探索の並列数を表す "concurrency" を使える
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルで、name は NUL 終端の文字列である必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_set_option(
+    engine: *const SearchEngine,
+    name: *const c_char,
+    value: i32,
+) -> i32 {
+    let name = unsafe { CStr::from_ptr(name) };
+    let engine = unsafe { &*engine };
+    name.to_str()
+        .is_ok_and(|name| engine.set_option(name, value)) as i32
+}
+
+/// エンジンの設定で choose_move_parallel_with と同じ探索をし、手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を、engine_stop によって探索が中断された場合は -2 を返す
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_choose_move(
+    engine: *const SearchEngine,
+    myself: u64,
+    opponent: u64,
+) -> i32 {
+    unsafe { &*engine }
+        .choose_move(myself, opponent)
+        .unwrap_or(-2)
+}
+
+/// エンジンの設定で choose_move_with_progress と同じ探索をし、手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を、engine_stop によって探索が中断された場合は -2 を返す
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルで、callback は NULL か、user_data を受け取って呼び出せる関数である必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_choose_move_with_progress(
+    engine: *const SearchEngine,
+    myself: u64,
+    opponent: u64,
+    interval_ms: u32,
+    callback: ProgressCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    let report = c_report(callback, user_data);
+    unsafe { &*engine }
+        .choose_move_with_progress(
+            myself,
+            opponent,
+            Duration::from_millis(interval_ms as u64),
+            report,
+        )
+        .map_or(-2, |progress| progress.best_move)
+}
+
+/// エンジンの設定で analyze と同じ解析をし、解析した手の数を返す
+///
+/// scores には、ビット番号ごとの手の評価値を長さ 64 の配列に書き込む（打てないマスには INTMIN を書き込む）
+///
+/// engine_stop によって探索が中断された場合は -2 を返し、scores には何も書き込まない
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルで、scores は書き込み可能な長さ 64 の配列を指す必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_analyze(
+    engine: *const SearchEngine,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    scores: *mut i32,
+) -> i32 {
+    match unsafe { &*engine }.analyze(myself, opponent, depth) {
+        Some(result) => {
+            let scores = unsafe { std::slice::from_raw_parts_mut(scores, 64) };
+            scores.fill(INTMIN);
+            for m in &result {
+                scores[m.index as usize] = m.score;
+            }
+            result.len() as i32
+        }
+        None => -2,
+    }
+}
+
+/// 実行中の engine_choose_move または engine_analyze と、既に呼び出されて順番を待っているものの中断を要求する
+///
+/// 探索を実行しているスレッドとは別のスレッドから呼び出す（探索中でない場合は何もしない）
+///
+/// # Safety
+///
+/// engine は engine_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn engine_stop(engine: *const SearchEngine) {
+    unsafe { &*engine }.stop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_position;
+    use crate::{analyze, choose_move_parallel_with, ffo_problems, full_search, place};
+    use std::thread;
+    use std::time::Duration;
+
+    // 序盤の局面
+    const MIDGAME: &str = "
+        --X--O--
+        ---XOO--
+        --OOXOO-
+        -OOOOOO-
+        ---OOO--
+        ----O---
+        --------
+        -------- X";
+
+    #[test]
+    fn engine_search_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let engine = SearchEngine::with_config(EngineConfig::DEFAULT, 2);
+        assert_eq!(
+            Some(choose_move_parallel_with(myself, opponent, 2)),
+            engine.choose_move(myself, opponent)
+        );
+        assert_eq!(
+            Some(analyze(myself, opponent, 3, 2)),
+            engine.analyze(myself, opponent, 3)
+        );
+        let mut depths = Vec::new();
+        let progress = engine
+            .choose_move_with_progress(myself, opponent, Duration::from_secs(60), |p| {
+                depths.push(p.depth)
+            })
+            .unwrap();
+        assert_eq!(
+            Some(progress.best_move),
+            engine.choose_move(myself, opponent)
+        );
+        assert_eq!(Some(&progress.depth), depths.last());
+        // 設定は次の探索に反映される
+        assert!(engine.set_option("parallel_wide_depth", 1));
+        assert!(engine.set_option("parallel_narrow_depth", 1));
+        assert_eq!(
+            Some(crate::heuristic_search(myself, opponent, 1)),
+            engine.choose_move(myself, opponent)
+        );
+    }
+
+    #[test]
+    fn engine_option_test() {
+        let engine = SearchEngine::new();
+        assert_eq!(Some(num_cpus::get() as i32), engine.option("concurrency"));
+        assert!(engine.set_option("concurrency", 3));
+        assert_eq!(3, engine.concurrency());
+        assert!(!engine.set_option("concurrency", 0));
+        assert_eq!(3, engine.concurrency());
+        assert!(engine.set_option("mobility_weight", 20));
+        assert_eq!(Some(20), engine.option("mobility_weight"));
+        assert_eq!(20, engine.config().mobility_weight);
+        assert!(!engine.set_option("unknown", 1));
+        assert_eq!(None, engine.option("unknown"));
+    }
+
+    #[test]
+    fn engine_stop_test() {
+        // 完全探索に長い時間がかかる局面の探索を中断する
+        let problem = &ffo_problems()[0];
+        let myself = problem.game.myself();
+        let opponent = problem.game.opponent();
+        let config = EngineConfig {
+            parallel_full_search_occupancy: 0,
+            ..EngineConfig::DEFAULT
+        };
+        let engine = SearchEngine::with_config(config, 1);
+        thread::scope(|scope| {
+            let handle = scope.spawn(|| engine.choose_move(myself, opponent));
+            // 呼び出される前の中断要求は無視されるので、探索が終わるまで繰り返し要求する
+            while !handle.is_finished() {
+                engine.stop();
+                thread::sleep(Duration::from_millis(1));
+            }
+            assert_eq!(None, handle.join().unwrap());
+        });
+        // 中断の後も探索を続けられ、探索が終わった後の中断要求は次の探索に影響しない
+        engine.set_config(EngineConfig::DEFAULT);
+        let (myself, opponent) = parse_position(MIDGAME);
+        assert!(engine.choose_move(myself, opponent).is_some());
+        engine.stop();
+        assert!(engine.choose_move(myself, opponent).is_some());
+    }
+
+    #[test]
+    fn engine_stop_waiting_test() {
+        // 他の探索の終了を待っている間に要求された中断も受け付ける
+        let (myself, opponent) = parse_position(MIDGAME);
+        let engine = SearchEngine::with_config(EngineConfig::DEFAULT, 1);
+        let guard = engine.search.lock().unwrap();
+        thread::scope(|scope| {
+            let handle = scope.spawn(|| engine.choose_move(myself, opponent));
+            while engine.control.lock().unwrap().entered == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            engine.stop();
+            drop(guard);
+            assert_eq!(None, handle.join().unwrap());
+        });
+        assert!(engine.choose_move(myself, opponent).is_some());
+    }
+
+    #[test]
+    fn engine_stop_after_search_test() {
+        let engine = SearchEngine::with_config(EngineConfig::DEFAULT, 1);
+        // 探索が中断要求に気づかずに戻った場合は、その後に届いた中断要求で結果を捨てない
+        assert_eq!(
+            Some(1),
+            engine.run(|_, _, _| {
+                engine.stop();
+                1
+            })
+        );
+        // 探索が中断要求に気づいて打ち切った場合は結果を捨てる
+        assert_eq!(
+            None,
+            engine.run(|_, _, shared| {
+                engine.stop();
+                shared.stop.check()
+            })
+        );
+    }
+
+    #[test]
+    fn engine_tables_test() {
+        // 探索の間で持ち続ける置換表によって、続く局面の探索の節点の数が減る
+        let (myself, opponent) =
+            parse_position("--XXXXX--OOOXX-O-OOOXXOX-OXOXOXXOXXXOXXX--XOXOXX-XXXOOO--OOOOO-- X");
+        let config = EngineConfig {
+            parallel_full_search_occupancy: 0,
+            ..EngineConfig::DEFAULT
+        };
+        let search = |engine: &SearchEngine, myself, opponent| {
+            engine
+                .choose_move_with_progress(myself, opponent, Duration::from_secs(60), |_| {})
+                .unwrap()
+        };
+        let engine = SearchEngine::with_config(config, 1);
+        let first = search(&engine, myself, opponent);
+        // 最良の手と、それに対する相手の最良の手を打った局面
+        let (mut s, mut o) = (0, 0);
+        place(myself, opponent, first.best_move, &mut s, &mut o);
+        let reply = full_search(o, s);
+        let (mut o2, mut s2) = (0, 0);
+        place(o, s, reply, &mut o2, &mut s2);
+        let fresh = search(&SearchEngine::with_config(config, 1), s2, o2);
+        let second = search(&engine, s2, o2);
+        assert_eq!(
+            (fresh.best_move, fresh.score),
+            (second.best_move, second.score)
+        );
+        // 表を持ち続けない場合は 30503 節点、持ち続ける場合は 26144 節点
+        assert!(
+            second.nodes < fresh.nodes,
+            "{} {}",
+            second.nodes,
+            fresh.nodes
+        );
+    }
+
+    #[test]
+    fn engine_handle_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let engine = engine_new();
+        let mut scores = [0; 64];
+        unsafe {
+            assert_eq!(1, engine_set_option(engine, c"concurrency".as_ptr(), 2));
+            assert_eq!(0, engine_set_option(engine, c"unknown".as_ptr(), 2));
+            assert_eq!(
+                choose_move_parallel_with(myself, opponent, 2),
+                engine_choose_move(engine, myself, opponent)
+            );
+            let result = analyze(myself, opponent, 3, 2);
+            assert_eq!(
+                result.len() as i32,
+                engine_analyze(engine, myself, opponent, 3, scores.as_mut_ptr())
+            );
+            for m in &result {
+                assert_eq!(m.score, scores[m.index as usize]);
+            }
+            assert_eq!(
+                result.len(),
+                scores.iter().filter(|&&score| score != INTMIN).count()
+            );
+            engine_free(engine);
+        }
+    }
+}
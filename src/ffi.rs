@@ -0,0 +1,568 @@
+use crate::{
+    choose_move, choose_move_parallel_with, full_search, full_search_parallel_with,
+    full_search_window, heuristic_search, heuristic_search_parallel_with, index_to_bit, perft,
+    place, possible_moves, turnovers,
+};
+use std::panic::{self, AssertUnwindSafe};
+
+/// 検査付きの関数が成功したことを表す戻り値
+pub const REVERSI_OK: i32 = 0;
+/// ビット番号や座標が盤の範囲外であることを表す戻り値
+pub const REVERSI_ERROR_INVALID_INDEX: i32 = -1;
+/// 2つのビットボードに共通のマスがあることを表す戻り値
+pub const REVERSI_ERROR_OVERLAPPING_BOARDS: i32 = -2;
+/// 先読みの深さが不正であることを表す戻り値
+pub const REVERSI_ERROR_INVALID_DEPTH: i32 = -3;
+/// 並列数が 1 未満であることを表す戻り値
+pub const REVERSI_ERROR_INVALID_CONCURRENCY: i32 = -4;
+/// 打てない場所に打とうとしたことを表す戻り値
+pub const REVERSI_ERROR_ILLEGAL_MOVE: i32 = -5;
+/// 結果を書き込むポインタが NULL であることを表す戻り値
+pub const REVERSI_ERROR_NULL_POINTER: i32 = -6;
+/// ライブラリの内部でパニックが起きたことを表す戻り値
+pub const REVERSI_ERROR_PANIC: i32 = -7;
+/// 探索の窓の下限が上限より大きいことを表す戻り値
+pub const REVERSI_ERROR_INVALID_WINDOW: i32 = -8;
+
+// 入力を検査する関数 f を呼び出し、成功した場合は結果を out に書き込んで REVERSI_OK を返す
+//
+// 失敗した場合は f が返したエラーの戻り値を返し、パニックは C 側へ伝えずに REVERSI_ERROR_PANIC を返す
+fn call<T>(out: *mut T, f: impl FnOnce() -> Result<T, i32>) -> i32 {
+    if out.is_null() {
+        return REVERSI_ERROR_NULL_POINTER;
+    }
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => {
+            unsafe { *out = value };
+            REVERSI_OK
+        }
+        Ok(Err(code)) => code,
+        Err(_) => REVERSI_ERROR_PANIC,
+    }
+}
+
+// ビット番号が盤の範囲内かを検査する
+fn check_index(n: i32) -> Result<(), i32> {
+    if (0..64).contains(&n) {
+        Ok(())
+    } else {
+        Err(REVERSI_ERROR_INVALID_INDEX)
+    }
+}
+
+// 2つのビットボードが重なっていないかを検査する
+fn check_boards(myself: u64, opponent: u64) -> Result<(), i32> {
+    if myself & opponent == 0 {
+        Ok(())
+    } else {
+        Err(REVERSI_ERROR_OVERLAPPING_BOARDS)
+    }
+}
+
+// 部分探索の先読みの深さが 1 以上かを検査する
+fn check_depth(depth: i32) -> Result<(), i32> {
+    if depth >= 1 {
+        Ok(())
+    } else {
+        Err(REVERSI_ERROR_INVALID_DEPTH)
+    }
+}
+
+// 並列数が 1 以上かを検査する
+fn check_concurrency(concurrency: i32) -> Result<(), i32> {
+    if concurrency >= 1 {
+        Ok(())
+    } else {
+        Err(REVERSI_ERROR_INVALID_CONCURRENCY)
+    }
+}
+
+/// 入力を検査して index_to_bit の結果を out に書き込む
+///
+/// n が 0 以上 64 未満でない場合は REVERSI_ERROR_INVALID_INDEX を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn index_to_bit_checked(n: i32, out: *mut u64) -> i32 {
+    call(out, || {
+        check_index(n)?;
+        Ok(index_to_bit(n))
+    })
+}
+
+/// 入力を検査して position_to_bit の結果を out に書き込む
+///
+/// i と j がともに 0 以上 8 未満でない場合は REVERSI_ERROR_INVALID_INDEX を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn position_to_bit_checked(i: i32, j: i32, out: *mut u64) -> i32 {
+    call(out, || {
+        if !(0..8).contains(&i) || !(0..8).contains(&j) {
+            return Err(REVERSI_ERROR_INVALID_INDEX);
+        }
+        Ok(index_to_bit(j * 8 + i))
+    })
+}
+
+/// 入力を検査して possible_moves の結果を out に書き込む
+///
+/// myself と opponent が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn possible_moves_checked(myself: u64, opponent: u64, out: *mut u64) -> i32 {
+    call(out, || {
+        check_boards(myself, opponent)?;
+        Ok(possible_moves(myself, opponent))
+    })
+}
+
+/// 入力を検査して turnovers の結果を out に書き込む
+///
+/// index が範囲外の場合は REVERSI_ERROR_INVALID_INDEX を、盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn turnovers_checked(
+    myself: u64,
+    opponent: u64,
+    index: i32,
+    out: *mut u64,
+) -> i32 {
+    call(out, || {
+        check_index(index)?;
+        check_boards(myself, opponent)?;
+        Ok(turnovers(myself, opponent, index))
+    })
+}
+
+/// 入力を検査して place と同じく手を打ち、打った後の盤を nmyself と nopponent に、返した石を turns に書き込む
+///
+/// index が範囲外の場合は REVERSI_ERROR_INVALID_INDEX を、盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、index に打てない場合は REVERSI_ERROR_ILLEGAL_MOVE を返す
+///
+/// エラーの場合はどこにも書き込まない
+///
+/// # Safety
+///
+/// nmyself と nopponent は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+///
+/// turns は NULL か、書き込み可能な領域を指す必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn place_checked(
+    myself: u64,
+    opponent: u64,
+    index: i32,
+    nmyself: *mut u64,
+    nopponent: *mut u64,
+    turns: *mut u64,
+) -> i32 {
+    if nmyself.is_null() || nopponent.is_null() {
+        return REVERSI_ERROR_NULL_POINTER;
+    }
+    let mut boards = (0, 0, 0);
+    let code = call(&mut boards, || {
+        check_index(index)?;
+        check_boards(myself, opponent)?;
+        if possible_moves(myself, opponent) & index_to_bit(index) == 0 {
+            return Err(REVERSI_ERROR_ILLEGAL_MOVE);
+        }
+        let mut s: u64 = 0;
+        let mut o: u64 = 0;
+        let t = place(myself, opponent, index, &mut s, &mut o);
+        Ok((s, o, t))
+    });
+    if code == REVERSI_OK {
+        unsafe {
+            *nmyself = boards.0;
+            *nopponent = boards.1;
+            if !turns.is_null() {
+                *turns = boards.2;
+            }
+        }
+    }
+    code
+}
+
+/// 入力を検査して full_search の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn full_search_checked(myself: u64, opponent: u64, out: *mut i32) -> i32 {
+    call(out, || {
+        check_boards(myself, opponent)?;
+        Ok(full_search(myself, opponent))
+    })
+}
+
+/// 入力を検査して full_search_parallel_with の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、concurrency が 1 未満の場合は REVERSI_ERROR_INVALID_CONCURRENCY を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn full_search_parallel_with_checked(
+    myself: u64,
+    opponent: u64,
+    concurrency: i32,
+    out: *mut i32,
+) -> i32 {
+    call(out, || {
+        check_boards(myself, opponent)?;
+        check_concurrency(concurrency)?;
+        Ok(full_search_parallel_with(myself, opponent, concurrency))
+    })
+}
+
+/// 入力を検査して full_search_window と同じ探索をし、結果の種類を result に、石数の差またはその限界を score に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、lower が upper より大きい場合は REVERSI_ERROR_INVALID_WINDOW を返す
+///
+/// エラーの場合はどこにも書き込まない
+///
+/// # Safety
+///
+/// result と score は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn full_search_window_checked(
+    myself: u64,
+    opponent: u64,
+    lower: i32,
+    upper: i32,
+    result: *mut i32,
+    score: *mut i32,
+) -> i32 {
+    if result.is_null() || score.is_null() {
+        return REVERSI_ERROR_NULL_POINTER;
+    }
+    let mut values = (0, 0);
+    let code = call(&mut values, || {
+        check_boards(myself, opponent)?;
+        if lower > upper {
+            return Err(REVERSI_ERROR_INVALID_WINDOW);
+        }
+        let mut v = 0;
+        let r = unsafe { full_search_window(myself, opponent, lower, upper, &mut v) };
+        Ok((r, v))
+    });
+    if code == REVERSI_OK {
+        unsafe {
+            *result = values.0;
+            *score = values.1;
+        }
+    }
+    code
+}
+
+/// 入力を検査して heuristic_search の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、depth が 1 未満の場合は REVERSI_ERROR_INVALID_DEPTH を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn heuristic_search_checked(
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    out: *mut i32,
+) -> i32 {
+    call(out, || {
+        check_boards(myself, opponent)?;
+        check_depth(depth)?;
+        Ok(heuristic_search(myself, opponent, depth))
+    })
+}
+
+/// 入力を検査して heuristic_search_parallel_with の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、depth が 1 未満の場合は REVERSI_ERROR_INVALID_DEPTH を、concurrency が 1 未満の場合は REVERSI_ERROR_INVALID_CONCURRENCY を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn heuristic_search_parallel_with_checked(
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    concurrency: i32,
+    out: *mut i32,
+) -> i32 {
+    call(out, || {
+        check_boards(myself, opponent)?;
+        check_depth(depth)?;
+        check_concurrency(concurrency)?;
+        Ok(heuristic_search_parallel_with(
+            myself,
+            opponent,
+            depth,
+            concurrency,
+        ))
+    })
+}
+
+/// 入力を検査して choose_move の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn choose_move_checked(myself: u64, opponent: u64, out: *mut i32) -> i32 {
+    call(out, || {
+        check_boards(myself, opponent)?;
+        Ok(choose_move(myself, opponent))
+    })
+}
+
+/// 入力を検査して choose_move_parallel_with の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、concurrency が 1 未満の場合は REVERSI_ERROR_INVALID_CONCURRENCY を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn choose_move_parallel_with_checked(
+    myself: u64,
+    opponent: u64,
+    concurrency: i32,
+    out: *mut i32,
+) -> i32 {
+    call(out, || {
+        check_boards(myself, opponent)?;
+        check_concurrency(concurrency)?;
+        Ok(choose_move_parallel_with(myself, opponent, concurrency))
+    })
+}
+
+/// 入力を検査して perft の結果を out に書き込む
+///
+/// 盤が重なっている場合は REVERSI_ERROR_OVERLAPPING_BOARDS を、depth が負の場合は REVERSI_ERROR_INVALID_DEPTH を返す
+///
+/// # Safety
+///
+/// out は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn perft_checked(
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    out: *mut u64,
+) -> i32 {
+    call(out, || {
+        check_boards(myself, opponent)?;
+        if depth < 0 {
+            return Err(REVERSI_ERROR_INVALID_DEPTH);
+        }
+        Ok(perft(myself, opponent, depth))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::REVERSI_WINDOW_EXACT;
+    use crate::game::parse_position;
+    use std::ptr;
+
+    // 序盤の局面
+    const MIDGAME: &str = "
+        --X--O--
+        ---XOO--
+        --OOXOO-
+        -OOOOOO-
+        ---OOO--
+        ----O---
+        --------
+        -------- X";
+
+    #[test]
+    fn index_check_test() {
+        let mut bit = 0;
+        unsafe {
+            assert_eq!(REVERSI_OK, index_to_bit_checked(63, &mut bit));
+            assert_eq!(1 << 63, bit);
+            for n in [-1, 64, i32::MIN, i32::MAX] {
+                assert_eq!(
+                    REVERSI_ERROR_INVALID_INDEX,
+                    index_to_bit_checked(n, &mut bit)
+                );
+            }
+            assert_eq!(REVERSI_OK, position_to_bit_checked(2, 3, &mut bit));
+            assert_eq!(1 << 26, bit);
+            assert_eq!(
+                REVERSI_ERROR_INVALID_INDEX,
+                position_to_bit_checked(8, 0, &mut bit)
+            );
+            assert_eq!(
+                REVERSI_ERROR_INVALID_INDEX,
+                position_to_bit_checked(0, -1, &mut bit)
+            );
+            assert_eq!(1 << 26, bit);
+            assert_eq!(
+                REVERSI_ERROR_NULL_POINTER,
+                index_to_bit_checked(0, ptr::null_mut())
+            );
+        }
+        // 検査のない関数も範囲外のビット番号でパニックしない
+        assert_eq!(0, index_to_bit(64));
+        assert_eq!(0, index_to_bit(-1));
+    }
+
+    #[test]
+    fn board_check_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let mut moves = 0;
+        let mut index = 0;
+        unsafe {
+            assert_eq!(
+                REVERSI_OK,
+                possible_moves_checked(myself, opponent, &mut moves)
+            );
+            assert_eq!(possible_moves(myself, opponent), moves);
+            assert_eq!(
+                REVERSI_ERROR_OVERLAPPING_BOARDS,
+                possible_moves_checked(myself, myself | opponent, &mut moves)
+            );
+            assert_eq!(
+                REVERSI_ERROR_OVERLAPPING_BOARDS,
+                choose_move_checked(myself, myself, &mut index)
+            );
+            assert_eq!(
+                REVERSI_ERROR_OVERLAPPING_BOARDS,
+                turnovers_checked(myself, myself, 0, &mut moves)
+            );
+        }
+    }
+
+    #[test]
+    fn place_check_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let index = possible_moves(myself, opponent).trailing_zeros() as i32;
+        let (mut s, mut o, mut t) = (0, 0, 0);
+        unsafe {
+            assert_eq!(
+                REVERSI_OK,
+                place_checked(myself, opponent, index, &mut s, &mut o, &mut t)
+            );
+            let (mut es, mut eo) = (0, 0);
+            assert_eq!(place(myself, opponent, index, &mut es, &mut eo), t);
+            assert_eq!((es, eo), (s, o));
+            assert_eq!(
+                REVERSI_OK,
+                place_checked(myself, opponent, index, &mut s, &mut o, ptr::null_mut())
+            );
+            // 打てない場所に打とうとした場合は何も書き込まない
+            let (mut s, mut o) = (0, 0);
+            assert_eq!(
+                REVERSI_ERROR_ILLEGAL_MOVE,
+                place_checked(myself, opponent, 63, &mut s, &mut o, ptr::null_mut())
+            );
+            assert_eq!((0, 0), (s, o));
+            assert_eq!(
+                REVERSI_ERROR_INVALID_INDEX,
+                place_checked(myself, opponent, 64, &mut s, &mut o, ptr::null_mut())
+            );
+            assert_eq!(
+                REVERSI_ERROR_NULL_POINTER,
+                place_checked(myself, opponent, index, &mut s, ptr::null_mut(), &mut t)
+            );
+        }
+    }
+
+    #[test]
+    fn search_check_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let mut index = 0;
+        let mut count = 0;
+        unsafe {
+            assert_eq!(
+                REVERSI_OK,
+                heuristic_search_checked(myself, opponent, 3, &mut index)
+            );
+            assert_eq!(heuristic_search(myself, opponent, 3), index);
+            for depth in [0, -1] {
+                assert_eq!(
+                    REVERSI_ERROR_INVALID_DEPTH,
+                    heuristic_search_checked(myself, opponent, depth, &mut index)
+                );
+                assert_eq!(
+                    REVERSI_ERROR_INVALID_DEPTH,
+                    heuristic_search_parallel_with_checked(myself, opponent, depth, 2, &mut index)
+                );
+            }
+            assert_eq!(
+                REVERSI_ERROR_INVALID_CONCURRENCY,
+                heuristic_search_parallel_with_checked(myself, opponent, 3, 0, &mut index)
+            );
+            assert_eq!(
+                REVERSI_ERROR_INVALID_CONCURRENCY,
+                choose_move_parallel_with_checked(myself, opponent, -1, &mut index)
+            );
+            assert_eq!(
+                REVERSI_ERROR_INVALID_CONCURRENCY,
+                full_search_parallel_with_checked(myself, opponent, 0, &mut index)
+            );
+            assert_eq!(REVERSI_OK, perft_checked(myself, opponent, 2, &mut count));
+            assert_eq!(perft(myself, opponent, 2), count);
+            assert_eq!(
+                REVERSI_ERROR_INVALID_DEPTH,
+                perft_checked(myself, opponent, -1, &mut count)
+            );
+        }
+    }
+
+    #[test]
+    fn window_check_test() {
+        let (myself, opponent) = parse_position(&format!("OX{} X", "-".repeat(62)));
+        let mut result = 1;
+        let mut score = 0;
+        unsafe {
+            assert_eq!(
+                REVERSI_OK,
+                full_search_window_checked(myself, opponent, -3, -3, &mut result, &mut score)
+            );
+            assert_eq!((REVERSI_WINDOW_EXACT, -3), (result, score));
+            assert_eq!(
+                REVERSI_ERROR_INVALID_WINDOW,
+                full_search_window_checked(myself, opponent, 1, 0, &mut result, &mut score)
+            );
+            assert_eq!(
+                REVERSI_ERROR_OVERLAPPING_BOARDS,
+                full_search_window_checked(myself, myself, 0, 0, &mut result, &mut score)
+            );
+            assert_eq!(
+                REVERSI_ERROR_NULL_POINTER,
+                full_search_window_checked(myself, opponent, 0, 0, ptr::null_mut(), &mut score)
+            );
+        }
+        assert_eq!((REVERSI_WINDOW_EXACT, -3), (result, score));
+    }
+
+    #[test]
+    fn panic_test() {
+        // パニックは戻り値に変換され、結果は書き込まれない
+        let mut value = 1;
+        let code = call(&mut value, || -> Result<i32, i32> { panic!("unexpected") });
+        assert_eq!(REVERSI_ERROR_PANIC, code);
+        assert_eq!(1, value);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::game::{Game, parse_board, parse_square};
+
+/// 終盤の問題の局面と、その局面で打てる手の完全探索による評価
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndgameProblem {
+    /// 問題の局面
+    pub game: Game,
+    /// 手のビット番号と、その手を打った場合の終局時の石数の差の組
+    pub move_scores: Vec<(i32, i32)>,
+}
+
+impl EndgameProblem {
+    /// 最善手を打った場合の終局時の石数の差を返す
+    pub fn score(&self) -> Option<i32> {
+        self.move_scores.iter().map(|&(_, score)| score).max()
+    }
+
+    /// 最善手のビット番号をすべて返す
+    pub fn best_moves(&self) -> Vec<i32> {
+        let score = self.score();
+        self.move_scores
+            .iter()
+            .filter(|&&(_, s)| Some(s) == score)
+            .map(|&(index, _)| index)
+            .collect()
+    }
+}
+
+/// 埋め込まれた FFO の終盤問題集の最初の問題の番号
+pub const FFO_FIRST_NUMBER: u32 = 40;
+
+// FFO の終盤問題集の #40 から順に並べた局面（OBF 形式）
+//
+// 各局面の最善手と石数の差は、公表されている解答と独立した完全探索の結果が一致することを確かめてある
+//
+// 今のところ #40 と #41 だけを収めている（#42 以降は OBF 形式のファイルに書いて reversi ffo <file> で解く）
+const FFO_PROBLEMS: &str = "\
+O--OOOOX-OOOOOOXOOXXOOOXOOXOOOXXOOOOOOXX---OOOOX----O--X-------- X; A2:+38;
+-OOOOO----OOOOX--OOOOOO-XXXXXOO--XXOOX--OOXOXX----OXXO---OOO--O- X; H4:+0;
+";
+
+/// 埋め込まれた FFO の終盤問題集の問題を番号の順に返す
+pub fn ffo_problems() -> Vec<EndgameProblem> {
+    FFO_PROBLEMS
+        .lines()
+        .map(|line| parse_obf(line).unwrap())
+        .collect()
+}
+
+/// Edax などが用いる OBF 形式の1行（"<局面の表記>; A2:+38; B1:+36;" のような局面と手の評価の並び）を読み込む
+///
+/// 局面の表記は parse_board が読める形式で、手の評価は省略してもよい
+///
+/// 表記として解釈できない場合は None を返す
+pub fn parse_obf(line: &str) -> Option<EndgameProblem> {
+    let mut fields = line.split(';');
+    let game = parse_board(fields.next()?)?;
+    let mut move_scores = Vec::new();
+    for field in fields.map(str::trim).filter(|f| !f.is_empty()) {
+        let (square, score) = field.split_once(':')?;
+        let index = parse_square(square.trim())?;
+        let score = score.trim().trim_start_matches('+').parse().ok()?;
+        move_scores.push((index, score));
+    }
+    Some(EndgameProblem { game, move_scores })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{full_search_parallel_stats, full_search_stats};
+
+    #[test]
+    fn obf_test() {
+        let problem = parse_obf(
+            "---------------------------OX------XO--------------------------- X; d3:+0; C4:+0; f5:0;",
+        )
+        .unwrap();
+        assert_eq!(Game::new(), problem.game);
+        assert_eq!(Some(0), problem.score());
+        assert_eq!(3, problem.best_moves().len());
+        let problem = parse_obf(
+            "---------------------------OX------XO--------------------------- X; d3:-2; c4:+4;",
+        )
+        .unwrap();
+        assert_eq!(Some(4), problem.score());
+        assert_eq!(vec![parse_square("c4").unwrap()], problem.best_moves());
+        assert_eq!(None, parse_obf("---- X; A2:+38;"));
+        assert_eq!(
+            None,
+            parse_obf("---------------------------OX------XO--------------------------- X; A9:+1;")
+        );
+        assert!(!ffo_problems().is_empty());
+    }
+
+    #[test]
+    fn ffo_quick_test() {
+        // 空きマスが最も少ない #40 だけを解く（すべての問題は ffo_test で解く）
+        let problem = &ffo_problems()[0];
+        let result = full_search_stats(problem.game.myself(), problem.game.opponent());
+        assert_eq!(vec![parse_square("a2").unwrap()], problem.best_moves());
+        assert_eq!((Some(38), 38), (problem.score(), result.score));
+        assert!(problem.best_moves().contains(&result.best_move));
+    }
+
+    // 完全探索に時間がかかるため、cargo test -- --ignored で明示的に実行する
+    #[test]
+    #[ignore]
+    fn ffo_test() {
+        for problem in ffo_problems() {
+            let myself = problem.game.myself();
+            let opponent = problem.game.opponent();
+            let result = full_search_stats(myself, opponent);
+            assert!(problem.best_moves().contains(&result.best_move));
+            assert_eq!(problem.score(), Some(result.score));
+            let result = full_search_parallel_stats(myself, opponent, num_cpus::get() as i32);
+            assert!(problem.best_moves().contains(&result.best_move));
+            assert_eq!(problem.score(), Some(result.score));
+        }
+    }
+}
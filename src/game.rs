@@ -0,0 +1,407 @@
+use crate::{balance, count_bits, index_to_bit, place, possible_moves};
+use std::fmt;
+
+/// 対局の進行を表す盤面と手番、および待ったのための履歴
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Game {
+    black: u64,
+    white: u64,
+    black_to_move: bool,
+    history: Vec<(u64, u64, bool)>,
+}
+
+/// 対局に対する不正な操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameError {
+    /// 打てない位置に打とうとした
+    IllegalMove,
+    /// 打てる手があるのにパスしようとした
+    IllegalPass,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::IllegalMove => write!(f, "illegal move"),
+            GameError::IllegalPass => write!(f, "illegal pass"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+impl Game {
+    /// 初期局面から始まる対局を返す
+    pub fn new() -> Game {
+        Game::from_position(0x0000_0008_1000_0000, 0x0000_0010_0800_0000, true)
+    }
+
+    /// "f5d6c3" のような着手の表記を連ねた棋譜を初期局面から打ち進めた対局を返す
+    ///
+    /// パスは表記しなくてよく、パスするしかない場合は自動でパスする（"pa" と書いてもよい）
+    pub fn from_transcript(text: &str) -> Result<Game, GameError> {
+        let mut game = Game::new();
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        for k in (0..text.len()).step_by(2) {
+            let token = text.get(k..k + 2).ok_or(GameError::IllegalMove)?;
+            if token.eq_ignore_ascii_case("pa") {
+                game.pass()?;
+                continue;
+            }
+            if game.must_pass() {
+                game.pass()?;
+            }
+            game.play(parse_square(token).ok_or(GameError::IllegalMove)?)?;
+        }
+        Ok(game)
+    }
+
+    /// 黒石 black と白石 white の局面から始まる対局を返す
+    pub fn from_position(black: u64, white: u64, black_to_move: bool) -> Game {
+        Game {
+            black,
+            white,
+            black_to_move,
+            history: Vec::new(),
+        }
+    }
+
+    /// 黒石のビットボード表現を返す
+    pub fn black(&self) -> u64 {
+        self.black
+    }
+
+    /// 白石のビットボード表現を返す
+    pub fn white(&self) -> u64 {
+        self.white
+    }
+
+    /// 黒の手番かどうかを返す
+    pub fn black_to_move(&self) -> bool {
+        self.black_to_move
+    }
+
+    /// 手番のプレイヤーの石のビットボード表現を返す
+    pub fn myself(&self) -> u64 {
+        if self.black_to_move {
+            self.black
+        } else {
+            self.white
+        }
+    }
+
+    /// 手番でないプレイヤーの石のビットボード表現を返す
+    pub fn opponent(&self) -> u64 {
+        if self.black_to_move {
+            self.white
+        } else {
+            self.black
+        }
+    }
+
+    /// 手番のプレイヤーが打てる手のビットボード表現を返す
+    pub fn legal_moves(&self) -> u64 {
+        possible_moves(self.myself(), self.opponent())
+    }
+
+    /// 手番のプレイヤーがパスしなければならないかを返す
+    pub fn must_pass(&self) -> bool {
+        self.legal_moves() == 0 && !self.is_over()
+    }
+
+    /// 両者とも打てる手がなく終局しているかを返す
+    pub fn is_over(&self) -> bool {
+        self.legal_moves() == 0 && possible_moves(self.opponent(), self.myself()) == 0
+    }
+
+    /// これまでに打たれた手（パスを含む）の数を返す
+    pub fn ply(&self) -> usize {
+        self.history.len()
+    }
+
+    /// 黒石の数から白石の数を引いたものを返す
+    pub fn score(&self) -> i32 {
+        balance(self.black, self.white)
+    }
+
+    /// 手番のプレイヤーが index の位置に打つ
+    pub fn play(&mut self, index: i32) -> Result<(), GameError> {
+        if !(0..64).contains(&index) || self.legal_moves() & index_to_bit(index) == 0 {
+            return Err(GameError::IllegalMove);
+        }
+        let mut s: u64 = 0;
+        let mut o: u64 = 0;
+        place(self.myself(), self.opponent(), index, &mut s, &mut o);
+        self.history
+            .push((self.black, self.white, self.black_to_move));
+        if self.black_to_move {
+            (self.black, self.white) = (s, o);
+        } else {
+            (self.white, self.black) = (s, o);
+        }
+        self.black_to_move = !self.black_to_move;
+        Ok(())
+    }
+
+    /// 手番のプレイヤーがパスする
+    ///
+    /// 打てる手がある場合と終局している場合はパスできない
+    pub fn pass(&mut self) -> Result<(), GameError> {
+        if !self.must_pass() {
+            return Err(GameError::IllegalPass);
+        }
+        self.history
+            .push((self.black, self.white, self.black_to_move));
+        self.black_to_move = !self.black_to_move;
+        Ok(())
+    }
+
+    /// 直前の手（パスを含む）を取り消し、取り消せたかを返す
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some((black, white, black_to_move)) => {
+                self.black = black;
+                self.white = white;
+                self.black_to_move = black_to_move;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// ビット番号を "d3" のような座標の表記に変換する
+pub fn square_name(index: i32) -> String {
+    let column = (b'a' + (index & 0x07) as u8) as char;
+    let row = (b'1' + (index >> 3) as u8) as char;
+    format!("{column}{row}")
+}
+
+/// "d3" や "D3" のような座標の表記をビット番号に変換する
+pub fn parse_square(text: &str) -> Option<i32> {
+    let bytes = text.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let column = bytes[0].to_ascii_lowercase();
+    let row = bytes[1];
+    if (b'a'..=b'h').contains(&column) && (b'1'..=b'8').contains(&row) {
+        Some((((row - b'1') as i32) << 3) | (column - b'a') as i32)
+    } else {
+        None
+    }
+}
+
+/// 64 マスの盤面の文字列と手番の文字からなる局面の表記を読み込み、その局面から始まる対局を返す
+///
+/// マスは a1, b1, ..., h1, a2, ..., h8 の順に並べ、黒石は "X" か "*"、白石は "O"、空きマスは "-" か "." で表す
+///
+/// 手番も同じ文字で表し、空白は無視する
+///
+/// 表記として解釈できない場合は None を返す
+pub fn parse_board(text: &str) -> Option<Game> {
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if chars.len() != 65 {
+        return None;
+    }
+    let mut black = 0;
+    let mut white = 0;
+    for (i, c) in chars[..64].iter().enumerate() {
+        match c {
+            'X' | 'x' | '*' => black |= 1 << i,
+            'O' | 'o' => white |= 1 << i,
+            '-' | '.' => (),
+            _ => return None,
+        }
+    }
+    let black_to_move = match chars[64] {
+        'X' | 'x' | '*' => true,
+        'O' | 'o' => false,
+        _ => return None,
+    };
+    Some(Game::from_position(black, white, black_to_move))
+}
+
+/// 局面を parse_board が読める表記（64 マスの文字列、空白、手番の文字）に変換する
+///
+/// 黒石は "X"、白石は "O"、空きマスは "-" で表す
+pub fn format_board(game: &Game) -> String {
+    let mut text: String = (0..64).map(|i| square_char(game, i)).collect();
+    text.push(' ');
+    text.push(if game.black_to_move() { 'X' } else { 'O' });
+    text
+}
+
+/// 局面を座標つきの盤の図に変換する
+///
+/// 黒石は "X"、白石は "O"、手番のプレイヤーが打てる空きマスは "."、その他の空きマスは "-" で表し、最後の行に手番と石数を示す
+pub fn render_board(game: &Game) -> String {
+    let moves = game.legal_moves();
+    let mut text = String::from("  a b c d e f g h\n");
+    for row in 0..8 {
+        text.push((b'1' + row as u8) as char);
+        for column in 0..8 {
+            let i = row * 8 + column;
+            text.push(' ');
+            text.push(if moves & index_to_bit(i) != 0 {
+                '.'
+            } else {
+                square_char(game, i)
+            });
+        }
+        text.push('\n');
+    }
+    let side = if game.is_over() {
+        "game over"
+    } else if game.black_to_move() {
+        "X to move"
+    } else {
+        "O to move"
+    };
+    text.push_str(&format!(
+        "{side} (X: {}, O: {})\n",
+        count_bits(game.black()),
+        count_bits(game.white())
+    ));
+    text
+}
+
+// index の位置のマスを表す文字を返す
+fn square_char(game: &Game, index: i32) -> char {
+    let bit = index_to_bit(index);
+    if game.black() & bit != 0 {
+        'X'
+    } else if game.white() & bit != 0 {
+        'O'
+    } else {
+        '-'
+    }
+}
+
+// テストで用いる局面の表記を読み込み、手番のプレイヤーの石と相手の石のビットボード表現を返す
+#[cfg(test)]
+pub(crate) fn parse_position(text: &str) -> (u64, u64) {
+    let game = parse_board(text).unwrap();
+    (game.myself(), game.opponent())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position_to_index;
+
+    #[test]
+    fn square_name_test() {
+        assert_eq!("a1", square_name(0));
+        assert_eq!("h1", square_name(7));
+        assert_eq!("d3", square_name(position_to_index(3, 2)));
+        assert_eq!("h8", square_name(63));
+        assert_eq!(Some(position_to_index(5, 4)), parse_square("f5"));
+        assert_eq!(Some(position_to_index(5, 4)), parse_square("F5"));
+        assert_eq!(None, parse_square("i1"));
+        assert_eq!(None, parse_square("a9"));
+        assert_eq!(None, parse_square("pass"));
+        for i in 0..64 {
+            assert_eq!(Some(i), parse_square(&square_name(i)));
+        }
+    }
+
+    #[test]
+    fn game_test() {
+        let mut game = Game::new();
+        assert!(game.black_to_move());
+        let moves = ["d3", "c4", "f5", "e6"].map(|t| index_to_bit(parse_square(t).unwrap()));
+        assert_eq!(moves.iter().fold(0, |m, b| m | b), game.legal_moves());
+        assert_eq!(Err(GameError::IllegalMove), game.play(0));
+        assert_eq!(Err(GameError::IllegalPass), game.pass());
+        game.play(parse_square("f5").unwrap()).unwrap();
+        assert!(!game.black_to_move());
+        assert_eq!(3, game.score());
+        game.play(parse_square("f6").unwrap()).unwrap();
+        assert_eq!(0, game.score());
+        assert_eq!(2, game.ply());
+        assert!(game.undo());
+        assert!(game.undo());
+        assert!(!game.undo());
+        assert_eq!(Game::new(), game);
+    }
+
+    #[test]
+    fn pass_test() {
+        // 白が打てず黒だけが打てる局面
+        let mut game = parse_board(&format!("XO{} O", "-".repeat(62))).unwrap();
+        assert!(game.must_pass());
+        game.pass().unwrap();
+        assert!(game.black_to_move());
+        assert_eq!(Err(GameError::IllegalPass), game.pass());
+        // 盤が埋まった局面
+        let game = parse_board(&format!("{}{} X", "O".repeat(32), "X".repeat(32))).unwrap();
+        assert!(game.is_over());
+        assert!(!game.must_pass());
+        assert_eq!(0, game.score());
+    }
+
+    #[test]
+    fn transcript_test() {
+        let game = Game::from_transcript("f5d6C3 d3").unwrap();
+        assert_eq!(4, game.ply());
+        assert!(game.black_to_move());
+        assert_eq!(
+            Err(GameError::IllegalMove),
+            Game::from_transcript("f5f5").map(|_| ())
+        );
+        assert_eq!(
+            Err(GameError::IllegalMove),
+            Game::from_transcript("f5d").map(|_| ())
+        );
+        assert_eq!(Ok(Game::new()), Game::from_transcript(""));
+    }
+
+    #[test]
+    fn parse_board_test() {
+        let start = "---------------------------OX------XO--------------------------- X";
+        assert_eq!(Some(Game::new()), parse_board(start));
+        assert_eq!(Some(Game::new()), parse_board(&start.replace('X', "*")));
+        let game = parse_board(&start.replace(" X", " O")).unwrap();
+        assert!(!game.black_to_move());
+        assert_eq!(None, parse_board(&start[1..]));
+        assert_eq!(None, parse_board(&start.replace(" X", " ?")));
+    }
+
+    #[test]
+    fn format_board_test() {
+        let start = "---------------------------OX------XO--------------------------- X";
+        assert_eq!(start, format_board(&Game::new()));
+        let game = Game::from_transcript("f5d6c3d3c4").unwrap();
+        let parsed = parse_board(&format_board(&game)).unwrap();
+        assert_eq!(
+            (game.black(), game.white(), game.black_to_move()),
+            (parsed.black(), parsed.white(), parsed.black_to_move())
+        );
+        assert!(format_board(&game).ends_with(" O"));
+    }
+
+    #[test]
+    fn render_board_test() {
+        let expected = "  a b c d e f g h
+1 - - - - - - - -
+2 - - - - - - - -
+3 - - - . - - - -
+4 - - . O X - - -
+5 - - - X O . - -
+6 - - - - . - - -
+7 - - - - - - - -
+8 - - - - - - - -
+X to move (X: 2, O: 2)
+";
+        assert_eq!(expected, render_board(&Game::new()));
+        let game = parse_board(&format!("{}{} X", "O".repeat(32), "X".repeat(32))).unwrap();
+        assert!(render_board(&game).ends_with("game over (X: 32, O: 32)\n"));
+    }
+}
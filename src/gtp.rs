@@ -0,0 +1,288 @@
+use crate::choose_move;
+use crate::game::{Game, GameError, parse_square, square_name};
+
+/// GTP（Go Text Protocol）にならった行単位のプロトコルで 8x8 のリバーシを打つエンジン
+///
+/// 対応するコマンドは protocol_version, name, version, known_command, list_commands, quit, boardsize, clear_board, play, genmove, undo, final_score
+///
+/// 手は "d3" のような座標か "pass" で表し、色は "black"（"b"）か "white"（"w"）で表す
+pub struct GtpEngine {
+    game: Game,
+    quit: bool,
+}
+
+// 対応するコマンドの一覧
+const COMMANDS: [&str; 12] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "boardsize",
+    "clear_board",
+    "play",
+    "genmove",
+    "undo",
+    "final_score",
+];
+
+impl Default for GtpEngine {
+    fn default() -> Self {
+        GtpEngine::new()
+    }
+}
+
+impl GtpEngine {
+    /// 初期局面のエンジンを返す
+    pub fn new() -> GtpEngine {
+        GtpEngine {
+            game: Game::new(),
+            quit: false,
+        }
+    }
+
+    /// 現在の対局を返す
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// quit コマンドを受け取ったかどうかを返す
+    pub fn is_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// コマンドを1行処理し、末尾の空行を含む応答を返す
+    ///
+    /// 空行とコメントだけの行には応答しないので None を返す
+    pub fn handle(&mut self, line: &str) -> Option<String> {
+        let line = line.split('#').next().unwrap_or("");
+        let mut words = line.split_whitespace().peekable();
+        let id = words
+            .next_if(|w| w.bytes().all(|b| b.is_ascii_digit()))
+            .unwrap_or("");
+        let command = words.next()?;
+        let args: Vec<&str> = words.collect();
+        let result = self.execute(command, &args);
+        Some(match result {
+            Ok(text) if text.is_empty() => format!("={id}\n\n"),
+            Ok(text) => format!("={id} {text}\n\n"),
+            Err(text) => format!("?{id} {text}\n\n"),
+        })
+    }
+
+    // コマンドを実行し、成功した場合は応答の本文を、失敗した場合はエラーメッセージを返す
+    fn execute(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        match command {
+            "protocol_version" => Ok("2".to_string()),
+            "name" => Ok("ReversiAI".to_string()),
+            "version" => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            "known_command" => Ok(COMMANDS.contains(args.first().unwrap_or(&"")).to_string()),
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "quit" => {
+                self.quit = true;
+                Ok(String::new())
+            }
+            "boardsize" => match args.first().map(|a| a.parse::<i32>()) {
+                Some(Ok(8)) => Ok(String::new()),
+                Some(Ok(_)) => Err("unacceptable size".to_string()),
+                _ => Err("syntax error".to_string()),
+            },
+            "clear_board" => {
+                self.game = Game::new();
+                Ok(String::new())
+            }
+            "play" => {
+                let [color, vertex] = args else {
+                    return Err("syntax error".to_string());
+                };
+                let black = parse_color(color).ok_or("syntax error")?;
+                let index = if vertex.eq_ignore_ascii_case("pass") {
+                    None
+                } else {
+                    Some(parse_square(vertex).ok_or("syntax error")?)
+                };
+                let passed = self.turn(black)?;
+                let result = match index {
+                    Some(index) => self.game.play(index),
+                    None => self.game.pass(),
+                };
+                // 打てなかった場合は補ったパスも取り消す
+                if let Err(e) = result {
+                    if passed {
+                        self.game.undo();
+                    }
+                    return Err(e.to_string());
+                }
+                Ok(String::new())
+            }
+            "genmove" => {
+                let [color] = args else {
+                    return Err("syntax error".to_string());
+                };
+                let black = parse_color(color).ok_or("syntax error")?;
+                self.turn(black)?;
+                // 終局している場合は盤を変えずにパスを返す
+                if self.game.is_over() {
+                    return Ok("pass".to_string());
+                }
+                let index = choose_move(self.game.myself(), self.game.opponent());
+                if index < 0 {
+                    self.game.pass().map_err(|e| e.to_string())?;
+                    Ok("pass".to_string())
+                } else {
+                    self.game.play(index).map_err(|e| e.to_string())?;
+                    Ok(square_name(index))
+                }
+            }
+            "undo" => {
+                if self.game.undo() {
+                    Ok(String::new())
+                } else {
+                    Err("cannot undo".to_string())
+                }
+            }
+            "final_score" => {
+                let score = self.game.score();
+                Ok(if score > 0 {
+                    format!("B+{score}")
+                } else if score < 0 {
+                    format!("W+{}", -score)
+                } else {
+                    "0".to_string()
+                })
+            }
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    // black が黒を表すかどうかの色を手番にし、パスを補ったかを返す
+    //
+    // 手番でない色が指定された場合、手番のプレイヤーがパスするしかなければパスを補う
+    fn turn(&mut self, black: bool) -> Result<bool, String> {
+        if self.game.black_to_move() == black {
+            return Ok(false);
+        }
+        self.game
+            .pass()
+            .map_err(|_: GameError| "not this color's turn".to_string())?;
+        Ok(true)
+    }
+}
+
+// 色の表記を黒かどうかに変換する
+fn parse_color(text: &str) -> Option<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "b" | "black" => Some(true),
+        "w" | "white" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_board;
+
+    // コマンドと期待する応答の組を順に実行する
+    fn run(engine: &mut GtpEngine, script: &[(&str, &str)]) {
+        for (command, expected) in script {
+            let response = engine.handle(command).unwrap();
+            assert_eq!(format!("{expected}\n\n"), response, "{command}");
+        }
+    }
+
+    #[test]
+    fn administrative_session_test() {
+        let mut engine = GtpEngine::new();
+        run(
+            &mut engine,
+            &[
+                ("protocol_version", "= 2"),
+                ("1 name", "=1 ReversiAI"),
+                ("known_command genmove", "= true"),
+                ("known_command komi", "= false"),
+                ("komi 6.5", "? unknown command"),
+                ("boardsize 8", "="),
+                ("2 boardsize 19", "?2 unacceptable size"),
+                ("boardsize", "? syntax error"),
+            ],
+        );
+        assert_eq!(None, engine.handle("# comment"));
+        assert_eq!(None, engine.handle(""));
+        assert!(!engine.is_quit());
+        run(&mut engine, &[("quit", "=")]);
+        assert!(engine.is_quit());
+    }
+
+    #[test]
+    fn play_session_test() {
+        let mut engine = GtpEngine::new();
+        run(
+            &mut engine,
+            &[
+                ("clear_board", "="),
+                ("play black f5", "="),
+                ("play white f6", "="),
+                ("final_score", "= 0"),
+                ("play black a1", "? illegal move"),
+                ("play white e6", "? not this color's turn"),
+                ("play black pass", "? illegal pass"),
+                ("play black z9", "? syntax error"),
+                ("play red e6", "? syntax error"),
+                ("play black", "? syntax error"),
+                ("play b e6", "="),
+                ("final_score", "= B+3"),
+                ("undo", "="),
+                ("undo", "="),
+                ("undo", "="),
+                ("undo", "? cannot undo"),
+            ],
+        );
+        assert_eq!(&Game::new(), engine.game());
+    }
+
+    #[test]
+    fn genmove_session_test() {
+        let mut engine = GtpEngine::new();
+        // 終盤の局面で完全探索による手を返す
+        engine.game = parse_board(
+            "
+            XXOOXXXO
+            OOOOOXXO
+            OOXOOOXO
+            OOXXOXOO
+            OOOOXOOO
+            OOOXOOOO
+            O-OOOOOO
+            --OOOOXO X",
+        )
+        .unwrap();
+        run(&mut engine, &[("genmove black", "= a8")]);
+        // パスするしかない場合は pass を返す
+        engine.game = parse_board(&format!("XO{} O", "-".repeat(62))).unwrap();
+        run(
+            &mut engine,
+            &[
+                ("genmove white", "= pass"),
+                ("genmove black", "= c1"),
+                ("genmove white", "= pass"),
+                ("final_score", "= B+3"),
+            ],
+        );
+        assert!(engine.game().is_over());
+        // 黒の手番でパスが必要な場合、白の手にはパスを補う
+        engine.game = parse_board(&format!("OX{} X", "-".repeat(62))).unwrap();
+        run(
+            &mut engine,
+            &[
+                ("play white d1", "? illegal move"),
+                ("play white c1", "="),
+                ("final_score", "= W+3"),
+                ("undo", "="),
+                ("undo", "="),
+                ("undo", "? cannot undo"),
+            ],
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::BTreeSet;
+
+// C から呼び出せる関数と定数を定義しているソースファイル
+//
+// #[unsafe(no_mangle)] な関数を持つファイルを追加した場合はここにも加える
+const SOURCES: [(&str, &str); 9] = [
+    ("lib.rs", include_str!("lib.rs")),
+    ("config.rs", include_str!("config.rs")),
+    ("engine.rs", include_str!("engine.rs")),
+    ("ffi.rs", include_str!("ffi.rs")),
+    ("mcts.rs", include_str!("mcts.rs")),
+    ("ponder.rs", include_str!("ponder.rs")),
+    ("progress.rs", include_str!("progress.rs")),
+    ("strategy.rs", include_str!("strategy.rs")),
+    ("version.rs", include_str!("version.rs")),
+];
+
+// ヘッダーで宣言する1つの関数や定数
+struct Export {
+    // 宣言の前のドキュメントコメント
+    docs: Vec<String>,
+    // C での宣言
+    declaration: String,
+}
+
+// ソースファイルから読み取ったヘッダーの内容
+#[derive(Default)]
+struct Header {
+    // 不透明な型として宣言する構造体の名前
+    opaque: BTreeSet<String>,
+    // 関数ポインタ型の別名の名前
+    aliases: BTreeSet<String>,
+    // 関数ポインタ型の別名の宣言
+    typedefs: Vec<Export>,
+    // 定数の宣言
+    constants: Vec<Export>,
+    // 関数の宣言
+    exports: Vec<Export>,
+}
+
+/// C から呼び出せるすべての関数と定数を宣言する C のヘッダーファイル reversi_ai.h の内容を返す
+///
+/// 関数の宣言は #[unsafe(no_mangle)] が付いた関数のシグネチャから生成し、ドキュメントコメントも含める
+///
+/// 共有ライブラリを使う C のプログラムはこのヘッダーを include する
+pub fn c_header() -> String {
+    let mut parsed = Header::default();
+    // 関数ポインタ型の別名は、使われる場所より後で定義されていてもよい
+    for (_, source) in SOURCES {
+        for line in source.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("pub type ") {
+                parsed
+                    .aliases
+                    .insert(rest.split_once(' ').unwrap().0.to_string());
+            }
+        }
+    }
+    for (_, source) in SOURCES {
+        parsed.parse_source(source);
+    }
+    let mut header = String::new();
+    header.push_str("// このファイルは `reversi header` によって生成される（直接編集しない）\n\n");
+    header.push_str("#ifndef REVERSI_AI_H\n#define REVERSI_AI_H\n\n");
+    header.push_str("#include <stdint.h>\n\n");
+    header.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+    for name in &parsed.opaque {
+        header.push_str(&format!("typedef struct {name} {name};\n"));
+    }
+    for export in parsed
+        .typedefs
+        .iter()
+        .chain(&parsed.constants)
+        .chain(&parsed.exports)
+    {
+        header.push('\n');
+        for doc in &export.docs {
+            header.push_str(doc);
+            header.push('\n');
+        }
+        header.push_str(&export.declaration);
+        header.push('\n');
+    }
+    header.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n#endif\n");
+    header
+}
+
+impl Header {
+    // ソースファイルから公開された型の別名と定数と関数を読み取る
+    fn parse_source(&mut self, source: &str) {
+        let mut docs = Vec::new();
+        let mut lines = source.lines().map(str::trim);
+        while let Some(line) = lines.next() {
+            if line.starts_with("#[cfg(test)]") {
+                break;
+            } else if line.starts_with("///") {
+                docs.push(line.to_string());
+            } else if let Some(rest) = line.strip_prefix("pub type ") {
+                // 定義を ; まで読む
+                let mut definition = rest.to_string();
+                while !definition.ends_with(';') {
+                    definition.push(' ');
+                    definition.push_str(lines.next().unwrap());
+                }
+                let declaration = self.c_typedef(&definition);
+                self.typedefs.push(Export {
+                    docs: std::mem::take(&mut docs),
+                    declaration,
+                });
+            } else if let Some(rest) = line.strip_prefix("pub const REVERSI_") {
+                let (name, value) = rest.split_once(':').unwrap();
+                let value = value.split_once('=').unwrap().1;
+                let value = value.trim().trim_end_matches(';');
+                self.constants.push(Export {
+                    docs: std::mem::take(&mut docs),
+                    declaration: format!("#define REVERSI_{name} ({value})"),
+                });
+            } else if line == "#[unsafe(no_mangle)]" {
+                // シグネチャを本体の開き括弧まで読む
+                let mut signature = String::new();
+                for line in lines.by_ref() {
+                    if line.starts_with("#[") {
+                        continue;
+                    }
+                    signature.push_str(line);
+                    signature.push(' ');
+                    if line.ends_with('{') {
+                        break;
+                    }
+                }
+                let declaration = self.c_declaration(&signature);
+                self.exports.push(Export {
+                    docs: std::mem::take(&mut docs),
+                    declaration,
+                });
+            } else if !line.starts_with("#[") {
+                docs.clear();
+            }
+        }
+    }
+
+    // Rust の関数のシグネチャを C の関数の宣言に変換する
+    fn c_declaration(&mut self, signature: &str) -> String {
+        let rest = &signature[signature.find("fn ").unwrap() + 3..];
+        let (name, rest) = rest.split_once('(').unwrap();
+        let (args, ret) = self.c_function(rest);
+        format!("{}({args});", join_type(&ret, name.trim()))
+    }
+
+    // 関数ポインタ型の別名の定義 "Name = Option<unsafe extern "C" fn(...)>;" を C の typedef に変換する
+    fn c_typedef(&mut self, definition: &str) -> String {
+        let (name, rest) = definition.split_once('=').unwrap();
+        let rest = rest.trim().trim_end_matches(';').trim();
+        let rest = rest
+            .strip_prefix("Option<")
+            .unwrap()
+            .strip_suffix('>')
+            .unwrap();
+        let (_, rest) = rest.split_once("fn(").unwrap();
+        let rest = rest.trim().trim_end_matches(',');
+        let (args, ret) = self.c_function(rest);
+        format!("typedef {ret} (*{})({args});", name.trim())
+    }
+
+    // 引数の並びの開き括弧より後を受け取り、C の引数の並びと戻り値の型を返す
+    fn c_function(&mut self, rest: &str) -> (String, String) {
+        let (args, rest) = rest.rsplit_once(')').unwrap();
+        let ret = match rest.trim().trim_end_matches('{').trim().strip_prefix("->") {
+            Some(ty) => self.c_type(ty.trim()),
+            None => "void".to_string(),
+        };
+        let mut c_args = Vec::new();
+        for arg in args.split(',').map(str::trim).filter(|arg| !arg.is_empty()) {
+            let (name, ty) = arg.split_once(':').unwrap();
+            let ty = self.c_type(ty.trim());
+            c_args.push(join_type(&ty, name.trim()));
+        }
+        let args = if c_args.is_empty() {
+            "void".to_string()
+        } else {
+            c_args.join(", ")
+        };
+        (args, ret)
+    }
+
+    // Rust の型を C の型に変換する
+    //
+    // 別名でない未知の型は不透明な構造体として opaque に加える
+    fn c_type(&mut self, ty: &str) -> String {
+        if let Some(ty) = ty.strip_prefix("*mut ").or(ty.strip_prefix("&mut ")) {
+            format!("{} *", self.c_type(ty.trim()))
+        } else if let Some(ty) = ty.strip_prefix("*const ") {
+            format!("const {} *", self.c_type(ty.trim()))
+        } else {
+            match ty {
+                "i32" => "int32_t",
+                "u32" => "uint32_t",
+                "i64" => "int64_t",
+                "u64" => "uint64_t",
+                "f64" => "double",
+                "c_char" => "char",
+                "c_void" => "void",
+                _ => {
+                    if !self.aliases.contains(ty) {
+                        self.opaque.insert(ty.to_string());
+                    }
+                    ty
+                }
+            }
+            .to_string()
+        }
+    }
+}
+
+// C の型と名前を並べる（ポインタ型では * の後に空白を入れない）
+fn join_type(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{ty}{name}")
+    } else {
+        format!("{ty} {name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn declaration_test() {
+        let mut header = Header::default();
+        header.aliases.insert("Callback".to_string());
+        assert_eq!(
+            "int32_t count(uint64_t n);",
+            header.c_declaration("pub extern \"C\" fn count(n: u64) -> i32 {")
+        );
+        assert_eq!(
+            "Handle *handle_new(void);",
+            header.c_declaration("pub extern \"C\" fn handle_new() -> *mut Handle {")
+        );
+        assert_eq!(
+            "void handle_set(const Handle *handle, const char *name, uint64_t *value, Callback callback);",
+            header.c_declaration(
+                "pub unsafe extern \"C\" fn handle_set( handle: *const Handle, name: *const c_char, value: &mut u64, callback: Callback, ) {"
+            )
+        );
+        assert_eq!(
+            "typedef void (*Callback)(int32_t n, void *data);",
+            header.c_typedef(
+                "Callback = Option< unsafe extern \"C\" fn(n: i32, data: *mut c_void), >;"
+            )
+        );
+        assert_eq!(
+            vec!["Handle"],
+            header.opaque.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    // 生成したヘッダーが、リポジトリにあるヘッダーと一致することを確かめる
+    #[test]
+    fn header_test() {
+        let header = c_header();
+        assert_eq!(
+            include_str!("../include/reversi_ai.h"),
+            header,
+            "include/reversi_ai.h is out of date; regenerate it with `cargo run --bin reversi -- header > include/reversi_ai.h`"
+        );
+        // すべての公開された関数がヘッダーで宣言されている
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "rs") {
+                continue;
+            }
+            let source = fs::read_to_string(&path).unwrap();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            if source
+                .lines()
+                .any(|line| line.trim() == "#[unsafe(no_mangle)]")
+            {
+                assert!(
+                    SOURCES.iter().any(|&(file, _)| file == name),
+                    "{name} is missing from SOURCES"
+                );
+            }
+        }
+        let count = SOURCES
+            .iter()
+            .map(|(_, source)| {
+                source
+                    .lines()
+                    .filter(|line| line.trim() == "#[unsafe(no_mangle)]")
+                    .count()
+            })
+            .sum::<usize>();
+        assert_eq!(
+            count,
+            header
+                .lines()
+                .filter(|line| line.ends_with(");") && !line.starts_with("typedef"))
+                .count()
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use crate::random::Random;
+use crate::{
+    EngineConfig, INTMAX, INTMIN, NEVER_STOP, SearchContext, count_bits, full_search,
+    heuristic_search_sub, is_better_move, nth_move, place, possible_moves,
+};
+use std::cmp::max;
+
+/// 思考の強さを表す難易度
+///
+/// Novice が最も弱く、Maximum が最も強い
+///
+/// Maximum は choose_move と同じ強さで打つ
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Novice = 1,
+    Beginner = 2,
+    Casual = 3,
+    Easy = 4,
+    Intermediate = 5,
+    Skilled = 6,
+    Advanced = 7,
+    Expert = 8,
+    Master = 9,
+    Maximum = 10,
+}
+
+/// 難易度ごとの探索のパラメータ
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelParams {
+    /// 打てる手が多い局面での先読みの深さ
+    pub depth: i32,
+    /// 打てる手が少ない局面での先読みの深さ
+    pub narrow_depth: i32,
+    /// 空きマスがこの数以下になると完全探索をする
+    pub endgame_empties: i32,
+    /// 部分探索で各手の評価値に加える一様な雑音の振れ幅
+    pub noise: i32,
+    /// 探索をせずに無作為な手を打つ確率（百分率）
+    pub blunder_percent: u32,
+}
+
+impl Level {
+    /// すべての難易度を弱い順に並べたもの
+    pub const ALL: [Level; 10] = [
+        Level::Novice,
+        Level::Beginner,
+        Level::Casual,
+        Level::Easy,
+        Level::Intermediate,
+        Level::Skilled,
+        Level::Advanced,
+        Level::Expert,
+        Level::Master,
+        Level::Maximum,
+    ];
+
+    /// 1 から 10 までの番号に対応する難易度を返す
+    pub fn from_number(n: i32) -> Option<Level> {
+        if (1..=10).contains(&n) {
+            Some(Level::ALL[(n - 1) as usize])
+        } else {
+            None
+        }
+    }
+
+    /// 難易度の番号を返す
+    pub fn number(self) -> i32 {
+        self as i32
+    }
+
+    /// 難易度に対応する探索のパラメータを返す
+    pub fn params(self) -> LevelParams {
+        let (depth, narrow_depth, endgame_empties, noise, blunder_percent) = match self {
+            Level::Novice => (1, 1, 2, 4000, 30),
+            Level::Beginner => (1, 1, 4, 2000, 20),
+            Level::Casual => (1, 1, 6, 1000, 10),
+            Level::Easy => (3, 3, 6, 1000, 6),
+            Level::Intermediate => (3, 3, 8, 800, 4),
+            Level::Skilled => (3, 3, 10, 200, 1),
+            Level::Advanced => (3, 5, 10, 100, 0),
+            Level::Expert => (5, 5, 10, 0, 0),
+            Level::Master => (5, 5, 12, 0, 0),
+            Level::Maximum => (5, 7, 13, 0, 0),
+        };
+        LevelParams {
+            depth,
+            narrow_depth,
+            endgame_empties,
+            noise,
+            blunder_percent,
+        }
+    }
+
+    /// この難易度で探索をし、選んだ手のビット番号を返す
+    ///
+    /// 打つ手がない場合は -1 を返す
+    ///
+    /// 雑音と悪手は seed に基づいて決まり、同じ局面と同じ seed に対しては常に同じ手を返す
+    pub fn choose_move(self, myself: u64, opponent: u64, seed: u64) -> i32 {
+        choose_level_move(&EngineConfig::DEFAULT, self, myself, opponent, seed)
+    }
+}
+
+// 設定 config に従う Level::choose_move の本体
+pub(crate) fn choose_level_move(
+    config: &EngineConfig,
+    level: Level,
+    myself: u64,
+    opponent: u64,
+    seed: u64,
+) -> i32 {
+    let params = level.params();
+    let moves = possible_moves(myself, opponent);
+    if moves == 0 {
+        return -1;
+    }
+    let mut random = Random::new(seed);
+    // 一定の確率で無作為な手を打つ
+    if random.below(100) < params.blunder_percent as u64 {
+        return nth_move(moves, random.below(count_bits(moves) as u64) as i32);
+    }
+    let empties = 64 - count_bits(myself | opponent);
+    if empties <= params.endgame_empties {
+        return full_search(myself, opponent);
+    }
+    let move_count = max(
+        count_bits(moves),
+        count_bits(possible_moves(opponent, myself)),
+    );
+    let depth = if move_count > config.branching_threshold {
+        params.depth
+    } else {
+        params.narrow_depth
+    };
+    if params.noise > 0 {
+        noisy_search(config, myself, opponent, depth, params.noise, &mut random)
+    } else {
+        config.heuristic_search(myself, opponent, depth)
+    }
+}
+
+// 各手の評価値に -noise 以上 noise 以下の一様な雑音を加えた上で、最良の手のビット番号を返す
+fn noisy_search(
+    config: &EngineConfig,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    noise: i32,
+    random: &mut Random,
+) -> i32 {
+    let moves = possible_moves(myself, opponent);
+    let mut ctx = SearchContext::new(config, &NEVER_STOP);
+    let mut alpha = INTMIN;
+    let beta = INTMAX;
+    let mut m = moves;
+    let mut i = 0;
+    let d = depth - 1;
+    let mut chosen = -1;
+    loop {
+        if m & 0x01 != 0 {
+            let mut s: u64 = 0;
+            let mut o: u64 = 0;
+            let turns = place(myself, opponent, i, &mut s, &mut o);
+            let shift = config.openness_evaluation(myself, opponent, turns)
+                + random.below(2 * noise as u64 + 1) as i32
+                - noise;
+            // 雑音の分だけ窓をずらして探索する
+            let lower = max(alpha.saturating_sub(shift), INTMIN);
+            let v = -heuristic_search_sub(&mut ctx, o, s, d, 1, -beta, -lower) + shift;
+            if is_better_move(v, i, alpha, chosen) {
+                alpha = v;
+                chosen = i;
+            }
+        }
+        m >>= 1;
+        i += 1;
+        if m == 0 {
+            break;
+        }
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, parse_position};
+
+    // 2つの難易度で対局し、先手の石数から後手の石数を引いたものを返す
+    fn play(black: Level, white: Level, seed: u64) -> i32 {
+        let game = Game::new();
+        let (mut myself, mut opponent) = (game.myself(), game.opponent());
+        let mut black_turn = true;
+        let mut passed = false;
+        let mut ply = 0;
+        let mut random = Random::new(seed);
+        loop {
+            let level = if black_turn { black } else { white };
+            // 序盤の数手は無作為に打って局面を散らす
+            let i = if ply < 4 {
+                let moves = possible_moves(myself, opponent);
+                nth_move(moves, random.below(count_bits(moves) as u64) as i32)
+            } else {
+                level.choose_move(myself, opponent, random.next_u64())
+            };
+            if i < 0 {
+                if passed {
+                    break;
+                }
+                passed = true;
+            } else {
+                passed = false;
+                let mut s: u64 = 0;
+                let mut o: u64 = 0;
+                place(myself, opponent, i, &mut s, &mut o);
+                myself = s;
+                opponent = o;
+            }
+            (myself, opponent) = (opponent, myself);
+            black_turn = !black_turn;
+            ply += 1;
+        }
+        let b = count_bits(myself) - count_bits(opponent);
+        if black_turn { b } else { -b }
+    }
+
+    #[test]
+    fn level_conversion_test() {
+        for (k, level) in Level::ALL.iter().enumerate() {
+            assert_eq!(Some(*level), Level::from_number(k as i32 + 1));
+            assert_eq!(k as i32 + 1, level.number());
+        }
+        assert_eq!(None, Level::from_number(0));
+        assert_eq!(None, Level::from_number(11));
+    }
+
+    #[test]
+    fn level_params_test() {
+        // 難易度が上がるほど深く読み、雑音と悪手が減る
+        for pair in Level::ALL.windows(2) {
+            let (weak, strong) = (pair[0].params(), pair[1].params());
+            assert!(weak.depth <= strong.depth);
+            assert!(weak.narrow_depth <= strong.narrow_depth);
+            assert!(weak.endgame_empties <= strong.endgame_empties);
+            assert!(weak.noise >= strong.noise);
+            assert!(weak.blunder_percent >= strong.blunder_percent);
+        }
+        // 最強の難易度は choose_move と同じ手を打つ
+        let (myself, opponent) = parse_position(
+            "
+            --X--O--
+            ---XOO--
+            --OOXOO-
+            -OOOOOO-
+            ---OOO--
+            ----O---
+            --------
+            -------- X",
+        );
+        assert_eq!(
+            crate::choose_move(myself, opponent),
+            Level::Maximum.choose_move(myself, opponent, 0)
+        );
+    }
+
+    #[test]
+    fn level_strength_test() {
+        // 両端と中央の難易度で先後を入れ替えて対局し、強い難易度が大差で勝ち越す
+        let pairs = [
+            (Level::Novice, Level::Intermediate),
+            (Level::Intermediate, Level::Maximum),
+            (Level::Novice, Level::Maximum),
+        ];
+        for (weak, strong) in pairs {
+            let mut wins = 0;
+            let mut discs = 0;
+            let games = 32;
+            for seed in 0..games / 2 {
+                let b = play(strong, weak, seed);
+                let w = -play(weak, strong, seed);
+                wins += (b > 0) as i32 + (w > 0) as i32;
+                discs += b + w;
+            }
+            // 4 分の 3 以上の対局に勝ち、1 局あたり平均 8 石以上の差をつける
+            assert!(
+                wins * 4 >= games as i32 * 3 && discs >= 8 * games as i32,
+                "{strong:?} vs {weak:?}: {wins} wins in {games} games, {discs:+} discs"
+            );
+        }
+    }
+
+    #[test]
+    fn level_adjacent_strength_test() {
+        // 隣り合う難易度で先後を入れ替えて対局し、強い方が石数で大きく負け越さない
+        for pair in Level::ALL.windows(2) {
+            let (weak, strong) = (pair[0], pair[1]);
+            // Advanced までは対局が速く差もはっきりしているので、多く対局して平均 2 石以上の勝ち越しを求める
+            // それより上は対局に時間がかかり差も小さいので、少ない対局で平均 8 石を超える負け越しだけを検出する
+            let (games, margin) = if strong <= Level::Advanced {
+                (20, 2)
+            } else {
+                (8, -8)
+            };
+            let mut discs = 0;
+            for seed in 0..games / 2 {
+                discs += play(strong, weak, seed) - play(weak, strong, seed);
+            }
+            assert!(
+                discs >= margin * games as i32,
+                "{strong:?} vs {weak:?}: {discs:+} discs in {games} games"
+            );
+        }
+    }
+}
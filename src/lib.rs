@@ -1,7 +1,65 @@
 use std::cmp::max;
-use std::sync::mpsc;
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
+use std::sync::{Mutex, mpsc};
 use std::thread;
 
+mod config;
+mod endgame;
+mod engine;
+mod ffi;
+mod ffo;
+mod game;
+mod gtp;
+mod header;
+mod level;
+mod mcts;
+mod nboard;
+mod ordering;
+mod ponder;
+mod probcut;
+mod progress;
+mod random;
+mod strategy;
+mod table;
+mod tournament;
+mod version;
+
+pub use config::EngineConfig;
+pub use engine::SearchEngine;
+pub use ffi::{
+    REVERSI_ERROR_ILLEGAL_MOVE, REVERSI_ERROR_INVALID_CONCURRENCY, REVERSI_ERROR_INVALID_DEPTH,
+    REVERSI_ERROR_INVALID_INDEX, REVERSI_ERROR_INVALID_WINDOW, REVERSI_ERROR_NULL_POINTER,
+    REVERSI_ERROR_OVERLAPPING_BOARDS, REVERSI_ERROR_PANIC, REVERSI_OK,
+};
+pub use ffo::{EndgameProblem, FFO_FIRST_NUMBER, ffo_problems, parse_obf};
+pub use game::{
+    Game, GameError, format_board, parse_board, parse_square, render_board, square_name,
+};
+pub use gtp::GtpEngine;
+pub use header::c_header;
+pub use level::{Level, LevelParams};
+pub use mcts::{MctsConfig, MctsResult, mcts_search};
+pub use nboard::{NBoardEngine, parse_ggf};
+use ordering::MoveOrdering;
+pub use ponder::Ponder;
+pub use probcut::{ProbCutParameter, fit_probcut};
+pub use progress::{ProgressCallback, SearchProgress};
+use random::Random;
+pub use strategy::{
+    REVERSI_STRATEGY_ASPIRATION, REVERSI_STRATEGY_FULL_WINDOW, REVERSI_STRATEGY_MTDF,
+};
+use table::TranspositionTable;
+pub use tournament::{
+    Engine, MatchConfig, MatchResult, Sprt, SprtDecision, balanced_openings, play_match,
+};
+pub use version::{
+    REVERSI_ABI_VERSION, REVERSI_CAPABILITY_BOOK, REVERSI_CAPABILITY_CHECKED,
+    REVERSI_CAPABILITY_CONFIG, REVERSI_CAPABILITY_ENGINE, REVERSI_CAPABILITY_MCTS,
+    REVERSI_CAPABILITY_PARALLEL_SEARCH, REVERSI_CAPABILITY_PONDER, REVERSI_CAPABILITY_PROGRESS,
+    REVERSI_CAPABILITY_TIMED_SEARCH, capabilities, version,
+};
+
 const INTMAX: i32 = 2147483647;
 const INTMIN: i32 = -2147483647;
 
@@ -24,9 +82,11 @@ pub extern "C" fn position_to_index(i: i32, j: i32) -> i32 {
 }
 
 /// n 番目にだけビットを立たせたビットボード表現を返す
+///
+/// n が 0 以上 64 未満でない場合は 0 を返す
 #[unsafe(no_mangle)]
 pub extern "C" fn index_to_bit(n: i32) -> u64 {
-    0x01u64 << n
+    0x01u64.checked_shl(n as u32).unwrap_or(0)
 }
 
 /// 特定の座標位置に対応する場所にだけビットを立たせたビットボード表現を返す
@@ -115,6 +175,15 @@ pub extern "C" fn possible_moves(myself: u64, opponent: u64) -> u64 {
     moves & blank
 }
 
+// moves の中で n 番目（0 始まり）に小さいビット番号を返す
+fn nth_move(moves: u64, n: i32) -> i32 {
+    let mut m = moves;
+    for _ in 0..n {
+        m &= m - 1;
+    }
+    m.trailing_zeros() as i32
+}
+
 /// myself プレイヤーが index 地点に打ったときに返せる石のビットボード表現を返す
 #[unsafe(no_mangle)]
 pub extern "C" fn turnovers(myself: u64, opponent: u64, index: i32) -> u64 {
@@ -342,11 +411,7 @@ pub extern "C" fn sum_of_weights(disks: u64) -> i32 {
 /// myself プレイヤーに有利なほど大きな数が返る静的評価関数
 #[unsafe(no_mangle)]
 pub extern "C" fn evaluation(myself: u64, opponent: u64) -> i32 {
-    let b = count_bits(myself | opponent);
-    let k = 50 * b;
-    (sum_of_weights(myself) - sum_of_weights(opponent))
-        + k * (count_bits(possible_moves(myself, opponent))
-            - count_bits(possible_moves(opponent, myself)))
+    EngineConfig::DEFAULT.evaluation(myself, opponent)
 }
 
 /// turns 周りの開放度を返す
@@ -370,99 +435,408 @@ pub extern "C" fn openness(myself: u64, opponent: u64, turns: u64) -> i32 {
 /// 係数を掛けた開放度の負値を返す
 #[unsafe(no_mangle)]
 pub extern "C" fn openness_evaluation(myself: u64, opponent: u64, turns: u64) -> i32 {
-    // 開放度に掛ける適当な係数
-    const K: i32 = 10;
-    -K * openness(myself, opponent, turns)
+    EngineConfig::DEFAULT.openness_evaluation(myself, opponent, turns)
+}
+
+// 評価値 v の手 i が、評価値 best_v の手 best_i よりも良い手であるかを返す
+//
+// 評価値が等しい場合はビット番号が小さい方を良い手とする
+// 探索関数はすべてこの規則で手を選ぶので、逐次版と並列版や並列数の違いによって結果が変わらない
+fn is_better_move(v: i32, i: i32, best_v: i32, best_i: i32) -> bool {
+    v > best_v || (v == best_v && i < best_i)
+}
+
+// 探索の中断要求
+//
+// 探索が要求に気づいて打ち切ったかも記録し、要求が探索の結果に影響したかを判断できるようにする
+pub(crate) struct StopSignal {
+    // 中断が要求されているか
+    requested: AtomicBool,
+    // 探索が中断の要求に気づいて打ち切ったか
+    observed: AtomicBool,
+}
+
+impl StopSignal {
+    // 中断が要求されていない状態を作る
+    pub(crate) const fn new() -> Self {
+        StopSignal {
+            requested: AtomicBool::new(false),
+            observed: AtomicBool::new(false),
+        }
+    }
+
+    // 中断を要求する
+    pub(crate) fn request(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+    }
+
+    // 中断が要求されているかを返す（要求されていれば、探索が打ち切られたことを記録する）
+    pub(crate) fn check(&self) -> bool {
+        let requested = self.requested.load(Ordering::Relaxed);
+        if requested {
+            self.observed.store(true, Ordering::Relaxed);
+        }
+        requested
+    }
+
+    // 中断要求と記録を消し、探索が中断の要求に気づいて打ち切っていたかを返す
+    pub(crate) fn reset(&self) -> bool {
+        self.requested.store(false, Ordering::Relaxed);
+        self.observed.swap(false, Ordering::Relaxed)
+    }
+}
+
+// 探索をまたいで使い回す、完全探索の置換表と部分探索の手の順序付けの表の組
+//
+// 探索のスレッドはそれぞれ使われていない組を1つ取り出して使い、探索を終えたら戻す
+pub(crate) struct SearchTables {
+    // 使われていない表の組
+    free: Mutex<Vec<(TranspositionTable, MoveOrdering)>>,
+}
+
+impl SearchTables {
+    // 表の組を持たない状態を作る
+    pub(crate) fn new() -> Self {
+        SearchTables {
+            free: Mutex::new(Vec::new()),
+        }
+    }
+
+    // 使われていない表の組を取り出す（なければ空の表の組を作る）
+    fn take(&self) -> (TranspositionTable, MoveOrdering) {
+        self.free
+            .lock()
+            .unwrap()
+            .pop()
+            .unwrap_or_else(|| (TranspositionTable::new(), MoveOrdering::new()))
+    }
+
+    // 使い終えた表の組を戻す
+    fn put(&self, tables: (TranspositionTable, MoveOrdering)) {
+        self.free.lock().unwrap().push(tables);
+    }
+}
+
+// 探索のスレッドの間で共有する、中断要求と使い回す表
+pub(crate) struct SearchShared<'a> {
+    // 探索の中断要求
+    pub(crate) stop: &'a StopSignal,
+    // 探索をまたいで使い回す表（None の場合はスレッドごとに空の表を使う）
+    pub(crate) tables: Option<&'a SearchTables>,
+}
+
+// 中断されない探索の中断要求
+static NO_STOP_REQUEST: StopSignal = StopSignal::new();
+
+// 中断されず、表も使い回さない探索に渡す共有の状態
+static NEVER_STOP: SearchShared<'static> = SearchShared {
+    stop: &NO_STOP_REQUEST,
+    tables: None,
+};
+
+// スレッドごとの探索の状態
+//
+// 使い回す表から取り出した表は、状態を捨てるときに戻す
+struct SearchContext<'a> {
+    // 探索の設定
+    config: &'a EngineConfig,
+    // 中断要求と使い回す表
+    shared: &'a SearchShared<'a>,
+    // 探索した節点の数
+    nodes: u64,
+    // 探索した節点の数を他のスレッドから読めるように一定数ごとに加算していく共有のカウンタ
+    counter: Option<&'a AtomicU64>,
+    // 完全探索の置換表
+    table: TranspositionTable,
+    // 部分探索の手の順序付けに用いる表
+    ordering: MoveOrdering,
+}
+
+impl<'a> SearchContext<'a> {
+    fn new(config: &'a EngineConfig, shared: &'a SearchShared<'a>) -> Self {
+        let (table, ordering) = match shared.tables {
+            Some(tables) => tables.take(),
+            None => (TranspositionTable::new(), MoveOrdering::new()),
+        };
+        SearchContext {
+            config,
+            shared,
+            nodes: 0,
+            counter: None,
+            table,
+            ordering,
+        }
+    }
+
+    // counter に加算するまでに数える節点の数（2 の冪）
+    const COUNTER_INTERVAL: u64 = 1024;
+
+    // 探索した節点の数を共有のカウンタ counter にも加算していく状態を作る
+    fn with_counter(
+        config: &'a EngineConfig,
+        shared: &'a SearchShared<'a>,
+        counter: &'a AtomicU64,
+    ) -> Self {
+        let mut ctx = SearchContext::new(config, shared);
+        ctx.counter = Some(counter);
+        ctx
+    }
+
+    // 探索の中断が要求されているかを返す
+    fn stopped(&self) -> bool {
+        self.shared.stop.check()
+    }
+
+    // 節点を1つ数える
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        if let Some(counter) = self.counter
+            && self.nodes.is_multiple_of(SearchContext::COUNTER_INTERVAL)
+        {
+            counter.fetch_add(SearchContext::COUNTER_INTERVAL, Ordering::Relaxed);
+        }
+    }
+
+    // まだ共有のカウンタに加算していない節点の数を加算する
+    fn flush_counter(&self) {
+        if let Some(counter) = self.counter {
+            counter.fetch_add(
+                self.nodes % SearchContext::COUNTER_INTERVAL,
+                Ordering::Relaxed,
+            );
+        }
+    }
+}
+
+impl Drop for SearchContext<'_> {
+    fn drop(&mut self) {
+        if let Some(tables) = self.shared.tables {
+            let table = mem::replace(&mut self.table, TranspositionTable::new());
+            let ordering = mem::replace(&mut self.ordering, MoveOrdering::new());
+            tables.put((table, ordering));
+        }
+    }
 }
 
 // ゲーム木の完全探索のサブルーチン
-fn full_search_sub(myself: u64, opponent: u64, alpha: i32, beta: i32) -> i32 {
+//
+// 中断が要求された場合は意味のない値を返す
+fn full_search_sub(
+    ctx: &mut SearchContext,
+    myself: u64,
+    opponent: u64,
+    alpha: i32,
+    beta: i32,
+) -> i32 {
+    if ctx.stopped() {
+        return 0;
+    }
+    // 残りの空きマスが少なければ専用の完全探索に任せる
+    let empties = count_bits(empty_squares(myself, opponent));
+    if empties <= endgame::LAST_EMPTIES {
+        return endgame::solve_last(ctx, myself, opponent, alpha, beta);
+    }
+    ctx.count_node();
+    // 相手の確定石は返せないので、石数の差は 64 から相手の確定石の数の 2 倍を引いた値以下になる
+    if 64 - 2 * count_bits(opponent) <= alpha {
+        let upper = 64 - 2 * count_bits(endgame::stable_discs(opponent, myself));
+        if upper <= alpha {
+            return upper;
+        }
+    }
     let moves = possible_moves(myself, opponent);
-    if moves != 0 {
-        let mut alpha = alpha;
-        let mut m = moves;
-        let mut i = 0;
-        loop {
-            if m & 0x01 != 0 {
-                let mut s: u64 = 0;
-                let mut o: u64 = 0;
-                place(myself, opponent, i, &mut s, &mut o);
-                let v = -full_search_sub(o, s, -beta, -alpha);
-                if v > alpha {
-                    alpha = v;
-                    if alpha >= beta {
-                        break;
-                    }
-                }
+    if moves == 0 {
+        return if possible_moves(opponent, myself) != 0 {
+            -full_search_sub(ctx, opponent, myself, -beta, -alpha)
+        } else {
+            balance(myself, opponent)
+        };
+    }
+    let use_table = empties >= endgame::TABLE_EMPTIES;
+    if use_table && let Some((lower, upper)) = ctx.table.probe(myself, opponent) {
+        if lower >= beta {
+            return lower;
+        }
+        if upper <= alpha {
+            return upper;
+        }
+    }
+    // 子の局面を列挙する（空きマスが多い局面では、相手の打てる手が少ない手から順に並べる）
+    let sort = empties >= endgame::ORDER_EMPTIES;
+    let mut children = [(0, 0, 0); 64];
+    let mut len = 0;
+    let mut m = moves;
+    while m != 0 {
+        let i = m.trailing_zeros() as i32;
+        m &= m - 1;
+        let mut s: u64 = 0;
+        let mut o: u64 = 0;
+        place(myself, opponent, i, &mut s, &mut o);
+        // 置換表にある子の局面の上界だけで窓の上限を超える手があれば、探索せずに打ち切る
+        if use_table
+            && let Some((_, upper)) = ctx.table.probe(o, s)
+            && -upper >= beta
+        {
+            return -upper;
+        }
+        let key = if sort {
+            let replies = possible_moves(o, s);
+            (count_bits(replies) + count_bits(replies & 0x8100_0000_0000_0081)) * 64 + i
+        } else {
+            i
+        };
+        children[len] = (key, o, s);
+        len += 1;
+    }
+    if sort {
+        children[..len].sort_unstable_by_key(|&(key, _, _)| key);
+    }
+    let mut value = alpha;
+    for (k, &(_, o, s)) in children[..len].iter().enumerate() {
+        // 最初の手以外は value を超えるかを null window で調べ、超える場合だけ探索し直す
+        let v = if k == 0 {
+            -full_search_sub(ctx, o, s, -beta, -value)
+        } else {
+            let v = -full_search_sub(ctx, o, s, -value - 1, -value);
+            if v > value && v < beta {
+                -full_search_sub(ctx, o, s, -beta, -value)
+            } else {
+                v
             }
-            m >>= 1;
-            i += 1;
-            if m == 0 {
+        };
+        if v > value {
+            value = v;
+            if value >= beta {
                 break;
             }
         }
-        alpha
-    } else {
-        if possible_moves(opponent, myself) != 0 {
-            -full_search_sub(opponent, myself, -beta, -alpha)
-        } else {
-            balance(myself, opponent)
-        }
     }
+    // 中断された探索の値は意味がないので記録しない
+    if use_table && !ctx.stopped() {
+        ctx.table.store(myself, opponent, alpha, beta, value);
+    }
+    value
 }
 
 /// ミニマックス戦略に基づいてゲーム木の完全探索をし、最良の手のビット番号を返す
 ///
 /// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
 #[unsafe(no_mangle)]
 pub extern "C" fn full_search(myself: u64, opponent: u64) -> i32 {
+    full_search_stats(myself, opponent).best_move
+}
+
+/// 完全探索の結果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchStats {
+    /// 最良の手のビット番号（打つ手がない場合は -1）
+    pub best_move: i32,
+    /// 最良の手を打った場合の終局時の石数の差（打つ手がない場合はパスした後の値）
+    pub score: i32,
+    /// 探索した節点の数
+    pub nodes: u64,
+}
+
+/// full_search と同じ完全探索をし、最良の手とともに終局時の石数の差と探索した節点の数を返す
+pub fn full_search_stats(myself: u64, opponent: u64) -> SearchStats {
+    let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, &NEVER_STOP);
     let moves = possible_moves(myself, opponent);
     if moves == 0 {
-        return -1;
+        let score = full_search_sub(&mut ctx, myself, opponent, INTMIN, INTMAX);
+        return SearchStats {
+            best_move: -1,
+            score,
+            nodes: ctx.nodes,
+        };
     }
-    let mut alpha = INTMIN;
-    let beta = INTMAX;
+    // 相手の打てる手が少ない手から順に調べる
+    let mut children = [(0, 0, 0, 0); 64];
+    let mut len = 0;
     let mut m = moves;
-    let mut i = 0;
-    let mut chosen = 0;
-    loop {
-        if m & 0x01 != 0 {
-            let mut s: u64 = 0;
-            let mut o: u64 = 0;
-            place(myself, opponent, i, &mut s, &mut o);
-            let v = -full_search_sub(o, s, -beta, -alpha);
-            if v > alpha {
-                alpha = v;
-                chosen = i;
+    while m != 0 {
+        let i = m.trailing_zeros() as i32;
+        m &= m - 1;
+        let mut s: u64 = 0;
+        let mut o: u64 = 0;
+        place(myself, opponent, i, &mut s, &mut o);
+        children[len] = (count_bits(possible_moves(o, s)), i, o, s);
+        len += 1;
+    }
+    children[..len].sort_unstable_by_key(|&(key, i, _, _)| (key, i));
+    let mut alpha = INTMIN;
+    let mut chosen = -1;
+    for &(_, i, o, s) in &children[..len] {
+        let v = if chosen < 0 {
+            -full_search_sub(&mut ctx, o, s, INTMIN, INTMAX)
+        } else {
+            // 評価が等しければビット番号の小さい手を選ぶので、その場合は1小さい値を超えるかを調べる
+            let bound = if i < chosen { alpha - 1 } else { alpha };
+            let v = -full_search_sub(&mut ctx, o, s, -bound - 1, -bound);
+            if v > bound {
+                -full_search_sub(&mut ctx, o, s, -INTMAX, -bound)
+            } else {
+                v
             }
-        }
-        m >>= 1;
-        i += 1;
-        if m == 0 {
-            break;
+        };
+        if is_better_move(v, i, alpha, chosen) {
+            alpha = v;
+            chosen = i;
         }
     }
-    chosen
+    SearchStats {
+        best_move: chosen,
+        score: alpha,
+        nodes: ctx.nodes + 1,
+    }
 }
 
 /// ミニマックス戦略に基づいてゲーム木の完全探索をし、最良の手のビット番号を返す
 ///
 /// 打つ手がない場合は -1 を返す
 ///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
 /// この関数は複数スレッドによって並列処理される
 #[unsafe(no_mangle)]
 pub extern "C" fn full_search_parallel_with(myself: u64, opponent: u64, concurrency: i32) -> i32 {
+    full_search_parallel_sub(myself, opponent, concurrency, &NEVER_STOP).best_move
+}
+
+/// full_search_parallel_with と同じ完全探索をし、最良の手とともに終局時の石数の差と探索した節点の数を返す
+///
+/// 節点の数はすべてのスレッドの合計
+pub fn full_search_parallel_stats(myself: u64, opponent: u64, concurrency: i32) -> SearchStats {
+    full_search_parallel_sub(myself, opponent, concurrency, &NEVER_STOP)
+}
+
+// 中断要求と使い回す表 shared を受け付ける full_search_parallel_stats の本体
+//
+// 中断が要求された場合は意味のない結果を返す
+fn full_search_parallel_sub(
+    myself: u64,
+    opponent: u64,
+    concurrency: i32,
+    shared: &SearchShared,
+) -> SearchStats {
     // 打てる手がなければ終了
     let moves = possible_moves(myself, opponent);
     if moves == 0 {
-        return -1;
+        let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, shared);
+        let score = full_search_sub(&mut ctx, myself, opponent, INTMIN, INTMAX);
+        return SearchStats {
+            best_move: -1,
+            score,
+            nodes: ctx.nodes,
+        };
     }
     // 探索をする
     let mut alpha = INTMIN;
     let mut m = moves;
     let mut i = 0;
-    let mut chosen = 0;
-    let result = thread::scope(|scope| {
+    let mut chosen = -1;
+    thread::scope(|scope| {
         let mut handles = Vec::new();
         let (sender, receiver) = mpsc::channel();
         let mut k = 0;
@@ -480,11 +854,15 @@ pub extern "C" fn full_search_parallel_with(myself: u64, opponent: u64, concurre
                 let sender = sender.clone();
                 // 完全探索のスレッド関数
                 let handle = scope.spawn(move || {
-                    let alpha: i32 = INTMIN;
-                    let beta: i32 = INTMAX;
-                    let value = -full_search_sub(o, s, -beta, -alpha);
+                    // 次のスレッドが表を使えるように、終了を知らせる前に探索の状態を捨てる
+                    let (value, nodes) = {
+                        let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, shared);
+                        let alpha: i32 = INTMIN;
+                        let beta: i32 = INTMAX;
+                        (-full_search_sub(&mut ctx, o, s, -beta, -alpha), ctx.nodes)
+                    };
                     sender.send(()).unwrap();
-                    value
+                    (value, nodes)
                 });
                 handles.push((i, handle));
             }
@@ -495,71 +873,190 @@ pub extern "C" fn full_search_parallel_with(myself: u64, opponent: u64, concurre
             }
         }
         // スレッドから結果を回収する
+        let mut nodes = 1;
         for (i, handle) in handles {
-            let v = handle.join().unwrap();
-            if v > alpha {
+            let (v, n) = handle.join().unwrap();
+            nodes += n;
+            if is_better_move(v, i, alpha, chosen) {
                 alpha = v;
                 chosen = i;
             }
         }
-        chosen
-    });
-    result
+        SearchStats {
+            best_move: chosen,
+            score: alpha,
+            nodes,
+        }
+    })
 }
 
 /// ミニマックス戦略に基づいてゲーム木の完全探索をし、最良の手のビット番号を返す
 ///
 /// 打つ手がない場合は -1 を返す
+///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
 #[unsafe(no_mangle)]
 pub extern "C" fn full_search_parallel(myself: u64, opponent: u64) -> i32 {
     let cpu_count = num_cpus::get() as i32;
     full_search_parallel_with(myself, opponent, cpu_count)
 }
 
+/// 窓付きの完全探索の結果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowResult {
+    /// 終局時の石数の差が窓の下限より小さい（値は石数の差の上界）
+    FailLow(i32),
+    /// 終局時の石数の差が窓の中にある（値は石数の差）
+    Exact(i32),
+    /// 終局時の石数の差が窓の上限より大きい（値は石数の差の下界）
+    FailHigh(i32),
+}
+
+/// 窓付きの完全探索の結果が、石数の差が窓の下限より小さいことを表す戻り値
+pub const REVERSI_WINDOW_FAIL_LOW: i32 = -1;
+/// 窓付きの完全探索の結果が、石数の差が窓の中にあることを表す戻り値
+pub const REVERSI_WINDOW_EXACT: i32 = 0;
+/// 窓付きの完全探索の結果が、石数の差が窓の上限より大きいことを表す戻り値
+pub const REVERSI_WINDOW_FAIL_HIGH: i32 = 1;
+
+/// 終局時の石数の差が lower 以上 upper 以下の範囲（窓）にあるかを完全探索で調べる
+///
+/// 石数の差が窓の中にある場合はその値を、窓の外にある場合はどちら側に外れたかとその向きの限界を返す
+///
+/// 窓が狭いほど探索は速い（「4 以上か」は lower = 4, upper = 64 で調べられる）
+///
+/// 打つ手がない場合はパスした後の値を調べる
+///
+/// # Panics
+///
+/// lower が upper より大きい場合
+pub fn full_search_bounded(myself: u64, opponent: u64, lower: i32, upper: i32) -> WindowResult {
+    assert!(lower <= upper, "empty window [{lower}, {upper}]");
+    // 石数の差は -64 以上 64 以下なので、窓の外側の値がはみ出さないように丸める
+    let alpha = lower.clamp(-64, 65) - 1;
+    let beta = upper.clamp(-65, 64) + 1;
+    let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, &NEVER_STOP);
+    let v = full_search_sub(&mut ctx, myself, opponent, alpha, beta);
+    if v <= alpha {
+        WindowResult::FailLow(v)
+    } else if v >= beta {
+        WindowResult::FailHigh(v)
+    } else {
+        WindowResult::Exact(v)
+    }
+}
+
+/// 終局時の石数の差が lower 以上 upper 以下の範囲にあるかを完全探索で調べ、結果の種類を返す
+///
+/// 石数の差が窓の中にある場合は REVERSI_WINDOW_EXACT を返し、score にその値を書き込む
+///
+/// 窓の下限より小さい場合は REVERSI_WINDOW_FAIL_LOW を返し、score に石数の差の上界を書き込む
+///
+/// 窓の上限より大きい場合は REVERSI_WINDOW_FAIL_HIGH を返し、score に石数の差の下界を書き込む
+///
+/// lower が upper より大きい場合は REVERSI_ERROR_INVALID_WINDOW を返し、どこにも書き込まない
+///
+/// # Safety
+///
+/// score は書き込み可能な領域を指す必要がある（NULL の場合は REVERSI_ERROR_NULL_POINTER を返す）
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn full_search_window(
+    myself: u64,
+    opponent: u64,
+    lower: i32,
+    upper: i32,
+    score: *mut i32,
+) -> i32 {
+    if score.is_null() {
+        return REVERSI_ERROR_NULL_POINTER;
+    }
+    if lower > upper {
+        return REVERSI_ERROR_INVALID_WINDOW;
+    }
+    let (code, v) = match full_search_bounded(myself, opponent, lower, upper) {
+        WindowResult::FailLow(v) => (REVERSI_WINDOW_FAIL_LOW, v),
+        WindowResult::Exact(v) => (REVERSI_WINDOW_EXACT, v),
+        WindowResult::FailHigh(v) => (REVERSI_WINDOW_FAIL_HIGH, v),
+    };
+    unsafe {
+        *score = v;
+    }
+    code
+}
+
 // ゲーム木の部分探索のサブルーチン
-fn heuristic_search_sub(myself: u64, opponent: u64, depth: i32, alpha: i32, beta: i32) -> i32 {
-    const CONFIDENT_VICTORY: i32 = 100000000;
+//
+// ply は探索の根からこの局面までの手数（パスを含む）で、手の順序付けの表を引くのに使う
+//
+// 中断が要求された場合は意味のない値を返す
+fn heuristic_search_sub(
+    ctx: &mut SearchContext,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    ply: usize,
+    alpha: i32,
+    beta: i32,
+) -> i32 {
+    if ctx.stopped() {
+        return 0;
+    }
+    ctx.count_node();
     let moves = possible_moves(myself, opponent);
     if moves != 0 {
         if depth != 0 {
+            if let Some(v) = probcut::probcut(ctx, myself, opponent, depth, ply, alpha, beta) {
+                return v;
+            }
             let mut alpha = alpha;
-            let mut m = moves;
-            let mut i = 0;
-            loop {
-                if m & 0x01 != 0 {
-                    let mut s: u64 = 0;
-                    let mut o: u64 = 0;
-                    place(myself, opponent, i, &mut s, &mut o);
-                    let v = -heuristic_search_sub(o, s, depth - 1, -beta, -alpha);
+            let mut best = INTMIN;
+            // キラームーブと history の表に従う順に調べる
+            let (order, len) = ctx.ordering.order(moves, ply);
+            for (k, &i) in order[..len].iter().enumerate() {
+                let mut s: u64 = 0;
+                let mut o: u64 = 0;
+                place(myself, opponent, i, &mut s, &mut o);
+                // 最初の手以外は alpha を超えるかを null window で調べ、超える場合だけ探索し直す
+                let v = if k == 0 {
+                    -heuristic_search_sub(ctx, o, s, depth - 1, ply + 1, -beta, -alpha)
+                } else {
+                    let v =
+                        -heuristic_search_sub(ctx, o, s, depth - 1, ply + 1, -alpha - 1, -alpha);
+                    if v > alpha && v < beta {
+                        -heuristic_search_sub(ctx, o, s, depth - 1, ply + 1, -beta, -alpha)
+                    } else {
+                        v
+                    }
+                };
+                if v > best {
+                    best = v;
                     if v > alpha {
                         alpha = v;
                         if alpha >= beta {
+                            if !ctx.stopped() {
+                                ctx.ordering.cutoff(i, ply, depth);
+                            }
                             break;
                         }
                     }
                 }
-                m >>= 1;
-                i += 1;
-                if m == 0 {
-                    break;
-                }
             }
-            alpha
+            best
         } else {
-            evaluation(myself, opponent)
+            ctx.config.evaluation(myself, opponent)
         }
     } else {
         if possible_moves(opponent, myself) != 0 {
             if depth != 0 {
-                -heuristic_search_sub(opponent, myself, depth - 1, -beta, -alpha)
+                -heuristic_search_sub(ctx, opponent, myself, depth - 1, ply + 1, -beta, -alpha)
             } else {
-                evaluation(myself, opponent)
+                ctx.config.evaluation(myself, opponent)
             }
         } else {
             if balance(myself, opponent) > 0 {
-                CONFIDENT_VICTORY
+                ctx.config.confident_victory
             } else {
-                -CONFIDENT_VICTORY
+                -ctx.config.confident_victory
             }
         }
     }
@@ -569,29 +1066,71 @@ fn heuristic_search_sub(myself: u64, opponent: u64, depth: i32, alpha: i32, beta
 ///
 /// 打つ手がない場合は -1 を返す
 ///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
 /// depth は先読みの深さで、1 以上である必要があり奇数が望ましい
 #[unsafe(no_mangle)]
 pub extern "C" fn heuristic_search(myself: u64, opponent: u64, depth: i32) -> i32 {
+    heuristic_search_root(&EngineConfig::DEFAULT, myself, opponent, depth)
+}
+
+// 根の手を打った後の局面 (o, s) を深さ depth で探索し、開放度の評価値 opns を加えた手の評価値を返す
+//
+// 評価値が best 以上 upper 未満の場合は正確な値を、best 未満の場合は best 未満の上界を、upper 以上の場合は upper 以上の下界を返す
+//
+// best 未満になるかを null window で調べ、そうでない場合だけ窓を広げて探索し直す
+fn search_root_move(
+    ctx: &mut SearchContext,
+    o: u64,
+    s: u64,
+    depth: i32,
+    opns: i32,
+    best: i32,
+    upper: i32,
+) -> i32 {
+    // 開放度を除いた評価値がこれ以上なら、評価値は upper 以上
+    let beta = upper.saturating_sub(opns).clamp(INTMIN + 1, INTMAX);
+    if best == INTMIN {
+        return -heuristic_search_sub(ctx, o, s, depth, 1, -beta, INTMAX) + opns;
+    }
+    // 開放度を除いた評価値がこれより大きければ、評価値は best 以上
+    let bound = best
+        .saturating_sub(opns)
+        .saturating_sub(1)
+        .clamp(INTMIN, INTMAX - 1);
+    let v = -heuristic_search_sub(ctx, o, s, depth, 1, -bound - 1, -bound);
+    if v > bound && v < beta {
+        -heuristic_search_sub(ctx, o, s, depth, 1, -beta, -bound) + opns
+    } else {
+        v + opns
+    }
+}
+
+// 設定 config に従う heuristic_search の本体
+fn heuristic_search_root(config: &EngineConfig, myself: u64, opponent: u64, depth: i32) -> i32 {
+    if config.root_strategy != REVERSI_STRATEGY_FULL_WINDOW {
+        return strategy::heuristic_search_iterative(config, myself, opponent, depth, &NEVER_STOP);
+    }
     // 打てる手がなければ終了
     let moves = possible_moves(myself, opponent);
     if moves == 0 {
         return -1;
     }
     // 探索をする
+    let mut ctx = SearchContext::new(config, &NEVER_STOP);
     let mut alpha = INTMIN;
-    let beta = INTMAX;
     let mut m = moves;
     let mut i = 0;
     let d = depth - 1;
-    let mut chosen = 0;
+    let mut chosen = -1;
     loop {
         if m & 0x01 != 0 {
             let mut s: u64 = 0;
             let mut o: u64 = 0;
             let turns = place(myself, opponent, i, &mut s, &mut o);
-            let v = -heuristic_search_sub(o, s, d, -beta, -alpha)
-                + openness_evaluation(myself, opponent, turns);
-            if v > alpha {
+            let opns = config.openness_evaluation(myself, opponent, turns);
+            let v = search_root_move(&mut ctx, o, s, d, opns, alpha, INTMAX);
+            if is_better_move(v, i, alpha, chosen) {
                 alpha = v;
                 chosen = i;
             }
@@ -609,6 +1148,8 @@ pub extern "C" fn heuristic_search(myself: u64, opponent: u64, depth: i32) -> i3
 ///
 /// 打つ手がない場合は -1 を返す
 ///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
 /// depth は先読みの深さで、1 以上である必要があり奇数が望ましい
 ///
 /// この関数は複数スレッドによって並列処理される
@@ -619,6 +1160,37 @@ pub extern "C" fn heuristic_search_parallel_with(
     depth: i32,
     concurrency: i32,
 ) -> i32 {
+    heuristic_search_parallel_sub(
+        &EngineConfig::DEFAULT,
+        myself,
+        opponent,
+        depth,
+        concurrency,
+        &NEVER_STOP,
+    )
+}
+
+// 設定 config と中断要求と使い回す表 shared を受け付ける heuristic_search_parallel_with の本体
+//
+// 中断が要求された場合は意味のない手を返す
+fn heuristic_search_parallel_sub(
+    config: &EngineConfig,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    concurrency: i32,
+    shared: &SearchShared,
+) -> i32 {
+    if config.root_strategy != REVERSI_STRATEGY_FULL_WINDOW {
+        return strategy::heuristic_search_parallel_iterative(
+            config,
+            myself,
+            opponent,
+            depth,
+            concurrency,
+            shared,
+        );
+    }
     // 打てる手がなければ終了
     let moves = possible_moves(myself, opponent);
     if moves == 0 {
@@ -628,8 +1200,10 @@ pub extern "C" fn heuristic_search_parallel_with(
     let mut alpha = INTMIN;
     let mut m = moves;
     let mut i = 0;
-    let mut chosen = 0;
-    let result = thread::scope(|scope| {
+    let mut chosen = -1;
+    // スレッド間で共有する、探索を終えた手の最良の評価値
+    let best = AtomicI32::new(INTMIN);
+    thread::scope(|scope| {
         let mut handles = Vec::new();
         let (sender, receiver) = mpsc::channel();
         let mut k = 0;
@@ -638,7 +1212,7 @@ pub extern "C" fn heuristic_search_parallel_with(
                 let mut s: u64 = 0;
                 let mut o: u64 = 0;
                 let turns = place(myself, opponent, i, &mut s, &mut o);
-                let opns = openness_evaluation(myself, opponent, turns);
+                let opns = config.openness_evaluation(myself, opponent, turns);
                 // 並列性を制限
                 if k >= concurrency {
                     receiver.recv().unwrap();
@@ -646,11 +1220,16 @@ pub extern "C" fn heuristic_search_parallel_with(
                     k += 1;
                 }
                 let sender = sender.clone();
+                let best = &best;
                 // 部分探索のスレッド関数
                 let handle = scope.spawn(move || {
-                    let alpha: i32 = INTMIN;
-                    let beta: i32 = INTMAX;
-                    let value = -heuristic_search_sub(o, s, depth - 1, -beta, -alpha) + opns;
+                    // 次のスレッドが表を使えるように、終了を知らせる前に探索の状態を捨てる
+                    let value = {
+                        let mut ctx = SearchContext::new(config, shared);
+                        let bound = best.load(Ordering::Relaxed);
+                        search_root_move(&mut ctx, o, s, depth - 1, opns, bound, INTMAX)
+                    };
+                    best.fetch_max(value, Ordering::Relaxed);
                     sender.send(()).unwrap();
                     value
                 });
@@ -665,20 +1244,21 @@ pub extern "C" fn heuristic_search_parallel_with(
         // スレッドから結果を回収する
         for (i, handle) in handles {
             let v = handle.join().unwrap();
-            if v > alpha {
+            if is_better_move(v, i, alpha, chosen) {
                 alpha = v;
                 chosen = i;
             }
         }
         chosen
-    });
-    result
+    })
 }
 
 /// ミニマックス戦略に基づいてゲーム木の部分探索をし、最良と思われる手のビット番号を返す
 ///
 /// 打つ手がない場合は -1 を返す
 ///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
 /// depth は先読みの深さで、1 以上である必要があり奇数が望ましい
 ///
 /// この関数は CPU スレッド数のスレッドによって並列処理される
@@ -688,31 +1268,107 @@ pub extern "C" fn heuristic_search_parallel(myself: u64, opponent: u64, depth: i
     heuristic_search_parallel_with(myself, opponent, depth, cpu_count)
 }
 
+/// ミニマックス戦略に基づいてゲーム木の部分探索をし、最良の手との評価値の差が margin 以内の手から無作為に選んだ手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// depth は先読みの深さで、1 以上である必要があり奇数が望ましい
+///
+/// margin が 0 の場合は評価値が最良の手の中から選ぶ（負の margin は 0 として扱う）
+///
+/// 選ばれる手は seed によって決まり、同じ局面と同じ seed に対しては常に同じ手を返す
+#[unsafe(no_mangle)]
+pub extern "C" fn heuristic_search_random(
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    margin: i32,
+    seed: u64,
+) -> i32 {
+    heuristic_search_random_sub(
+        &EngineConfig::DEFAULT,
+        myself,
+        opponent,
+        depth,
+        margin,
+        seed,
+    )
+}
+
+// 設定 config に従う heuristic_search_random の本体
+fn heuristic_search_random_sub(
+    config: &EngineConfig,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    margin: i32,
+    seed: u64,
+) -> i32 {
+    // 打てる手がなければ終了
+    let moves = possible_moves(myself, opponent);
+    if moves == 0 {
+        return -1;
+    }
+    // 負の margin では最良の手も候補から外れてしまうので 0 に丸める
+    let margin = margin.max(0);
+    // 探索をする
+    let mut ctx = SearchContext::new(config, &NEVER_STOP);
+    let mut best = INTMIN;
+    let beta = INTMAX;
+    let mut candidates = Vec::new();
+    let mut m = moves;
+    let mut i = 0;
+    let d = depth - 1;
+    loop {
+        if m & 0x01 != 0 {
+            let mut s: u64 = 0;
+            let mut o: u64 = 0;
+            let turns = place(myself, opponent, i, &mut s, &mut o);
+            let opns = config.openness_evaluation(myself, opponent, turns);
+            // 最良の手との差が margin 以内に収まる手は正確な評価値が得られる窓で探索する
+            let alpha = max(best.saturating_sub(margin).saturating_sub(1), INTMIN);
+            let v = -heuristic_search_sub(&mut ctx, o, s, d, 1, -beta, -(alpha - opns)) + opns;
+            if v > alpha {
+                candidates.push((i, v));
+                best = max(best, v);
+            }
+        }
+        m >>= 1;
+        i += 1;
+        if m == 0 {
+            break;
+        }
+    }
+    // 最良の手との差が margin 以内の手から選ぶ
+    let candidates: Vec<i32> = candidates
+        .into_iter()
+        .filter(|&(_, v)| v >= best.saturating_sub(margin))
+        .map(|(i, _)| i)
+        .collect();
+    let mut random = Random::new(seed);
+    candidates[random.below(candidates.len() as u64) as usize]
+}
+
 /// ミニマックス戦略に基づいてゲーム木の探索をし、最良と思われる手のビット番号を返す
 ///
 /// 打つ手がない場合は -1 を返す
 ///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
 /// ゲームの進行度によって部分探索と完全探索を自動で選択する
 ///
 /// 切り替えのタイミングと、先読みの深さは数秒で結果が返るような値に調整されている
 #[unsafe(no_mangle)]
 pub extern "C" fn choose_move(myself: u64, opponent: u64) -> i32 {
-    let occu = count_bits(myself | opponent);
-    if occu > 50 {
-        return full_search(myself, opponent);
-    } else {
-        let move_count = max(
-            count_bits(possible_moves(myself, opponent)),
-            count_bits(possible_moves(opponent, myself)),
-        );
-        return heuristic_search(myself, opponent, if move_count > 8 { 5 } else { 7 });
-    }
+    EngineConfig::DEFAULT.choose_move(myself, opponent)
 }
 
 /// ミニマックス戦略に基づいてゲーム木の探索をし、最良と思われる手のビット番号を返す
 ///
 /// 打つ手がない場合は -1 を返す
 ///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
 /// ゲームの進行度によって部分探索と完全探索を自動で選択する
 ///
 /// 切り替えのタイミングと、先読みの深さは数秒で結果が返るような値に調整されている
@@ -722,20 +1378,30 @@ pub extern "C" fn choose_move(myself: u64, opponent: u64) -> i32 {
 /// 並列処理によって探索にかかる時間が短くなるので非並列版よりも深く読むようにしている
 #[unsafe(no_mangle)]
 pub extern "C" fn choose_move_parallel_with(myself: u64, opponent: u64, concurrency: i32) -> i32 {
-    let occu = count_bits(myself | opponent);
-    if occu > 48 {
-        return full_search_parallel_with(myself, opponent, concurrency);
-    } else {
-        let move_count = max(
-            count_bits(possible_moves(myself, opponent)),
-            count_bits(possible_moves(opponent, myself)),
-        );
-        return heuristic_search_parallel_with(
-            myself,
-            opponent,
-            if move_count > 8 { 7 } else { 9 },
-            concurrency,
-        );
+    choose_move_parallel_sub(
+        &EngineConfig::DEFAULT,
+        myself,
+        opponent,
+        concurrency,
+        &NEVER_STOP,
+    )
+}
+
+// 設定 config と中断要求と使い回す表 shared を受け付ける choose_move_parallel_with の本体
+//
+// 中断が要求された場合は意味のない手を返す
+fn choose_move_parallel_sub(
+    config: &EngineConfig,
+    myself: u64,
+    opponent: u64,
+    concurrency: i32,
+    shared: &SearchShared,
+) -> i32 {
+    match config.parallel_search_depth(myself, opponent) {
+        None => full_search_parallel_sub(myself, opponent, concurrency, shared).best_move,
+        Some(depth) => {
+            heuristic_search_parallel_sub(config, myself, opponent, depth, concurrency, shared)
+        }
     }
 }
 
@@ -743,6 +1409,8 @@ pub extern "C" fn choose_move_parallel_with(myself: u64, opponent: u64, concurre
 ///
 /// 打つ手がない場合は -1 を返す
 ///
+/// 評価値が等しい手が複数ある場合は、ビット番号が最小の手を返す
+///
 /// ゲームの進行度によって部分探索と完全探索を自動で選択する
 ///
 /// 切り替えのタイミングと、先読みの深さは数秒で結果が返るような値に調整されている
@@ -756,9 +1424,228 @@ pub extern "C" fn choose_move_parallel(myself: u64, opponent: u64) -> i32 {
     choose_move_parallel_with(myself, opponent, cpu_count)
 }
 
+/// 局面の解析で得られる1つの手の評価
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveScore {
+    /// 手のビット番号
+    pub index: i32,
+    /// 評価値（exact の場合は終局時の石数の差）
+    pub score: i32,
+    /// 完全探索による正確な値かどうか
+    pub exact: bool,
+}
+
+/// 打てるすべての手をそれぞれ探索して評価し、評価値の高い順に並べて返す
+///
+/// 評価値が等しい手はビット番号の小さい順に並べる
+///
+/// depth が空きマスの数以上の場合は完全探索による終局時の石数の差を、そうでない場合は depth の深さの部分探索による評価値を返す
+///
+/// 部分探索の評価値は heuristic_search が手を選ぶときに用いる値と等しい
+///
+/// depth は 1 未満の場合は 1 として扱う
+///
+/// 各手の探索は concurrency 個のスレッドによって並列処理される
+pub fn analyze(myself: u64, opponent: u64, depth: i32, concurrency: i32) -> Vec<MoveScore> {
+    analyze_sub(
+        &EngineConfig::DEFAULT,
+        myself,
+        opponent,
+        depth,
+        concurrency,
+        &NEVER_STOP,
+    )
+}
+
+// 設定 config と中断要求と使い回す表 shared を受け付ける analyze の本体
+//
+// 中断が要求された場合は意味のない評価値を返す
+fn analyze_sub(
+    config: &EngineConfig,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    concurrency: i32,
+    shared: &SearchShared,
+) -> Vec<MoveScore> {
+    let depth = max(depth, 1);
+    let exact = depth >= 64 - count_bits(myself | opponent);
+    let mut m = possible_moves(myself, opponent);
+    let mut i = 0;
+    let mut scores: Vec<MoveScore> = thread::scope(|scope| {
+        let mut handles = Vec::new();
+        let (sender, receiver) = mpsc::channel();
+        let mut k = 0;
+        while m != 0 {
+            if m & 0x01 != 0 {
+                let mut s: u64 = 0;
+                let mut o: u64 = 0;
+                let turns = place(myself, opponent, i, &mut s, &mut o);
+                // 並列性を制限
+                if k >= concurrency {
+                    receiver.recv().unwrap();
+                } else {
+                    k += 1;
+                }
+                let sender = sender.clone();
+                let handle = scope.spawn(move || {
+                    // 次のスレッドが表を使えるように、終了を知らせる前に探索の状態を捨てる
+                    let value = {
+                        let mut ctx = SearchContext::new(config, shared);
+                        if exact {
+                            -full_search_sub(&mut ctx, o, s, INTMIN, INTMAX)
+                        } else {
+                            -heuristic_search_sub(&mut ctx, o, s, depth - 1, 1, INTMIN, INTMAX)
+                                + config.openness_evaluation(myself, opponent, turns)
+                        }
+                    };
+                    sender.send(()).unwrap();
+                    value
+                });
+                handles.push((i, handle));
+            }
+            m >>= 1;
+            i += 1;
+        }
+        handles
+            .into_iter()
+            .map(|(index, handle)| MoveScore {
+                index,
+                score: handle.join().unwrap(),
+                exact,
+            })
+            .collect()
+    });
+    scores.sort_by_key(|m| (-(m.score as i64), m.index));
+    scores
+}
+
+/// 手番のプレイヤーから depth 手先までのゲーム木の葉の数を返す
+///
+/// パスも1手として数え、途中で終局した局面はそこで葉として数える
+///
+/// 着手生成の検証や速度の計測に用いる
+#[unsafe(no_mangle)]
+pub extern "C" fn perft(myself: u64, opponent: u64, depth: i32) -> u64 {
+    if depth <= 0 {
+        return 1;
+    }
+    let mut m = possible_moves(myself, opponent);
+    if m == 0 {
+        return if possible_moves(opponent, myself) == 0 {
+            1
+        } else {
+            perft(opponent, myself, depth - 1)
+        };
+    }
+    let mut nodes = 0;
+    while m != 0 {
+        let i = m.trailing_zeros() as i32;
+        let mut s: u64 = 0;
+        let mut o: u64 = 0;
+        place(myself, opponent, i, &mut s, &mut o);
+        nodes += perft(o, s, depth - 1);
+        m &= m - 1;
+    }
+    nodes
+}
+
+/// ミニマックス戦略に基づいてゲーム木の探索をし、最良と思われる手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// ゲームの進行度によって部分探索と完全探索を自動で選択する
+///
+/// 部分探索では最良の手との評価値の差が margin 以内の手から seed に基づいて無作為に選ぶ
+///
+/// 完全探索では choose_move と同じく最良の手を選ぶ
+#[unsafe(no_mangle)]
+pub extern "C" fn choose_move_random(myself: u64, opponent: u64, margin: i32, seed: u64) -> i32 {
+    EngineConfig::DEFAULT.choose_move_random(myself, opponent, margin, seed)
+}
+
+/// 難易度 level に応じた強さで探索をし、選んだ手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// level は 1 から 10 までの整数で、大きいほど強い（範囲外の値は近い方の端に丸められる）
+///
+/// 10 では choose_move と同じ強さで打ち、低い難易度では浅い先読みに加えて評価値への雑音や意図的な悪手が入る
+///
+/// 選ばれる手は seed によって決まり、同じ局面と同じ seed に対しては常に同じ手を返す
+#[unsafe(no_mangle)]
+pub extern "C" fn choose_move_level(myself: u64, opponent: u64, level: i32, seed: u64) -> i32 {
+    let level = Level::from_number(level.clamp(1, 10)).unwrap();
+    level.choose_move(myself, opponent, seed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::{parse_position, parse_square};
+    use std::ptr;
+
+    // 初期局面
+    const START: &str = "
+        --------
+        --------
+        --------
+        ---OX---
+        ---XO---
+        --------
+        --------
+        -------- X";
+
+    // 序盤の局面
+    const MIDGAME: &str = "
+        --X--O--
+        ---XOO--
+        --OOXOO-
+        -OOOOOO-
+        ---OOO--
+        ----O---
+        --------
+        -------- X";
+
+    // 完全探索の対象となる終盤の局面
+    const ENDGAMES: [&str; 4] = [
+        "
+        XXOOXXXO
+        OOOOOXXO
+        OOXOOOXO
+        OOXXOXOO
+        OOOOXOOO
+        OOOXOOOO
+        O-OOOOOO
+        --OOOOXO X",
+        "
+        --OXXOOO
+        ---XXXOO
+        OOOOXOXO
+        OOOXXOOO
+        OOOXXXXO
+        -OOXXXOO
+        --XOOOOO
+        -XXXXXXX X",
+        "
+        OOOOOOOX
+        OOXXXOOX
+        OOOXOOOX
+        XXOOXXOX
+        XXXXOOOX
+        OOOOXOOX
+        --OOOOOX
+        -OOOOOOX X",
+        "
+        XOOOOX--
+        XXXOOO--
+        XOOXOXOO
+        XOXXOXXO
+        XOOOOOXO
+        XXOOOXXO
+        --OOXO-O
+        --OOOO-O X",
+    ];
 
     #[test]
     fn position_conversion_test() {
@@ -1246,89 +2133,379 @@ mod tests {
 
     #[test]
     fn full_search_test() {
+        for (board, best) in ENDGAMES.iter().zip(["a8", "b2", "a8", "b7"]) {
+            let (myself, opponent) = parse_position(board);
+            assert_eq!(parse_square(best), Some(full_search(myself, opponent)));
+            assert_eq!(
+                parse_square(best),
+                Some(full_search_parallel(myself, opponent))
+            );
+        }
+    }
+
+    #[test]
+    fn search_stats_test() {
+        // 最良の手と終局時の石数の差は full_search や analyze と一致する
+        for board in ENDGAMES {
+            let (myself, opponent) = parse_position(board);
+            let score = analyze(myself, opponent, 64, 1)[0].score;
+            let stats = full_search_stats(myself, opponent);
+            assert_eq!(full_search(myself, opponent), stats.best_move);
+            assert_eq!(score, stats.score);
+            assert!(stats.nodes > 1);
+            let stats = full_search_parallel_stats(myself, opponent, 2);
+            assert_eq!(full_search(myself, opponent), stats.best_move);
+            assert_eq!(score, stats.score);
+            assert!(stats.nodes > 1);
+        }
+        // 打つ手がない場合はパスした後の値を返す
+        let (myself, opponent) = parse_position(&format!("OX{} X", "-".repeat(62)));
+        let stats = full_search_stats(myself, opponent);
+        assert_eq!((-1, -3), (stats.best_move, stats.score));
+        let stats = full_search_parallel_stats(myself, opponent, 2);
+        assert_eq!((-1, -3), (stats.best_move, stats.score));
+    }
+
+    #[test]
+    fn window_search_test() {
+        for board in ENDGAMES {
+            let (myself, opponent) = parse_position(board);
+            let score = full_search_stats(myself, opponent).score;
+            assert_eq!(
+                WindowResult::Exact(score),
+                full_search_bounded(myself, opponent, -64, 64)
+            );
+            assert_eq!(
+                WindowResult::Exact(score),
+                full_search_bounded(myself, opponent, score, score)
+            );
+            // 窓の外にある場合は、外れた向きの限界を返す
+            match full_search_bounded(myself, opponent, score + 1, 64) {
+                WindowResult::FailLow(v) => assert!(score <= v && v < score + 1),
+                result => panic!("{result:?}"),
+            }
+            match full_search_bounded(myself, opponent, -64, score - 1) {
+                WindowResult::FailHigh(v) => assert!(v <= score && v > score - 1),
+                result => panic!("{result:?}"),
+            }
+            let mut v = 0;
+            unsafe {
+                assert_eq!(
+                    REVERSI_WINDOW_FAIL_HIGH,
+                    full_search_window(myself, opponent, i32::MIN, score - 1, &mut v)
+                );
+                assert_eq!(
+                    REVERSI_WINDOW_FAIL_LOW,
+                    full_search_window(myself, opponent, score + 1, i32::MAX, &mut v)
+                );
+                assert_eq!(
+                    REVERSI_WINDOW_EXACT,
+                    full_search_window(myself, opponent, score - 2, score + 2, &mut v)
+                );
+                assert_eq!(score, v);
+                // 不正な入力ではパニックせずにエラーを返し、どこにも書き込まない
+                assert_eq!(
+                    REVERSI_ERROR_INVALID_WINDOW,
+                    full_search_window(myself, opponent, score + 1, score, &mut v)
+                );
+                assert_eq!(
+                    REVERSI_ERROR_NULL_POINTER,
+                    full_search_window(myself, opponent, score, score, ptr::null_mut())
+                );
+                assert_eq!(score, v);
+            }
+        }
+        // 打つ手がない場合はパスした後の値を調べる
+        let (myself, opponent) = parse_position(&format!("OX{} X", "-".repeat(62)));
         assert_eq!(
-            position_to_index(0, 7),
-            full_search(0x4000_0810_2C44_6073, 0xBCFD_F7EF_D3BB_9F8C)
-        );
-        assert_eq!(
-            position_to_index(1, 1),
-            full_search(0xFE04_3878_1850_3818, 0x00F8_C687_E7AF_C0E4)
-        );
-        assert_eq!(
-            position_to_index(0, 7),
-            full_search(0x8080_908F_B388_9C80, 0x7E7C_6F70_4C77_637F)
-        );
-        assert_eq!(
-            position_to_index(1, 6),
-            full_search(0x0010_6341_6D29_0721, 0xBCAC_9CBE_92D6_381E)
-        );
-        assert_eq!(
-            position_to_index(0, 7),
-            full_search_parallel(0x4000_0810_2C44_6073, 0xBCFD_F7EF_D3BB_9F8C)
-        );
-        assert_eq!(
-            position_to_index(1, 1),
-            full_search_parallel(0xFE04_3878_1850_3818, 0x00F8_C687_E7AF_C0E4)
-        );
-        assert_eq!(
-            position_to_index(0, 7),
-            full_search_parallel(0x8080_908F_B388_9C80, 0x7E7C_6F70_4C77_637F)
+            WindowResult::Exact(-3),
+            full_search_bounded(myself, opponent, -10, 10)
         );
+    }
+
+    #[test]
+    fn endgame_nodes_test() {
+        // 確定石と置換表による枝刈りと手の並べ替えで、空きマスが 14 の局面の完全探索の節点の数が減る
+        let (myself, opponent) =
+            parse_position("--XXXXX--OOOXX-O-OOOXXOX-OXOXOXXOXXXOXXX--XOXOXX-XXXOOO--OOOOO-- X");
+        let stats = full_search_stats(myself, opponent);
         assert_eq!(
-            position_to_index(1, 6),
-            full_search_parallel(0x0010_6341_6D29_0721, 0xBCAC_9CBE_92D6_381E)
+            (parse_square("g8"), 18),
+            (Some(stats.best_move), stats.score)
         );
+        // 枝刈りを加える前は 1610604 節点、相手の打てる手が少ない手から並べる前は 1221131 節点
+        assert!(stats.nodes < 100_000, "{} nodes", stats.nodes);
+    }
+
+    #[test]
+    fn endgame_null_window_nodes_test() {
+        // 2 手目以降を null window で調べると、空きマスが 14 から 16 の局面の完全探索の節点の数が減る
+        let mut random = random::Random::new(7);
+        let mut nodes = 0;
+        let mut count = 0;
+        while count < 6 {
+            let discs = 48 + random.below(3) as i32;
+            if let Some((myself, opponent)) = probcut::sample_position(&mut random, discs)
+                && possible_moves(myself, opponent) != 0
+            {
+                nodes += full_search_stats(myself, opponent).nodes;
+                count += 1;
+            }
+        }
+        // null window を使う前は 1225398 節点
+        assert!(nodes < 1_150_000, "{nodes} nodes");
     }
 
     #[test]
     fn heuristic_search_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let best = parse_square("e1");
+        assert_eq!(best, Some(heuristic_search(myself, opponent, 9)));
+        assert_eq!(best, Some(heuristic_search_parallel(myself, opponent, 9)));
         assert_eq!(
-            position_to_index(4, 0),
-            heuristic_search(0x0000_0000_0010_0804, 0x0000_1038_7E6C_3020, 9)
-        );
-        assert_eq!(
-            position_to_index(4, 0),
-            heuristic_search_parallel(0x0000_0000_0010_0804, 0x0000_1038_7E6C_3020, 9)
-        );
-        assert_eq!(
-            position_to_index(4, 0),
-            heuristic_search_parallel_with(0x0000_0000_0010_0804, 0x0000_1038_7E6C_3020, 9, 1)
-        );
-        assert_eq!(
-            position_to_index(4, 0),
-            heuristic_search_parallel_with(0x0000_0000_0010_0804, 0x0000_1038_7E6C_3020, 9, 2)
-        );
-        assert_eq!(
-            heuristic_search(0x4000_0810_2C44_6073, 0xBCFD_F7EF_D3BB_9F8C, 7),
-            heuristic_search_parallel(0x4000_0810_2C44_6073, 0xBCFD_F7EF_D3BB_9F8C, 7)
-        );
-        assert_eq!(
-            heuristic_search(0xFE04_3878_1850_3818, 0x00F8_C687_E7AF_C0E4, 7),
-            heuristic_search_parallel(0xFE04_3878_1850_3818, 0x00F8_C687_E7AF_C0E4, 7)
-        );
-        assert_eq!(
-            heuristic_search(0x8080_908F_B388_9C80, 0x7E7C_6F70_4C77_637F, 7),
-            heuristic_search_parallel(0x8080_908F_B388_9C80, 0x7E7C_6F70_4C77_637F, 7)
-        );
-        assert_eq!(
-            heuristic_search(0x0010_6341_6D29_0721, 0xBCAC_9CBE_92D6_381E, 7),
-            heuristic_search_parallel(0x0010_6341_6D29_0721, 0xBCAC_9CBE_92D6_381E, 7)
+            best,
+            Some(heuristic_search_parallel_with(myself, opponent, 9, 1))
         );
         assert_eq!(
-            heuristic_search(0x4000_0810_2C44_6073, 0xBCFD_F7EF_D3BB_9F8C, 9),
-            heuristic_search_parallel(0x4000_0810_2C44_6073, 0xBCFD_F7EF_D3BB_9F8C, 9)
-        );
-        assert_eq!(
-            heuristic_search(0xFE04_3878_1850_3818, 0x00F8_C687_E7AF_C0E4, 9),
-            heuristic_search_parallel(0xFE04_3878_1850_3818, 0x00F8_C687_E7AF_C0E4, 9)
-        );
-        assert_eq!(
-            heuristic_search(0x8080_908F_B388_9C80, 0x7E7C_6F70_4C77_637F, 9),
-            heuristic_search_parallel(0x8080_908F_B388_9C80, 0x7E7C_6F70_4C77_637F, 9)
-        );
-        assert_eq!(
-            heuristic_search(0x0010_6341_6D29_0721, 0xBCAC_9CBE_92D6_381E, 9),
-            heuristic_search_parallel(0x0010_6341_6D29_0721, 0xBCAC_9CBE_92D6_381E, 9)
+            best,
+            Some(heuristic_search_parallel_with(myself, opponent, 9, 2))
         );
+        for board in ENDGAMES {
+            let (myself, opponent) = parse_position(board);
+            for depth in [7, 9] {
+                assert_eq!(
+                    heuristic_search(myself, opponent, depth),
+                    heuristic_search_parallel(myself, opponent, depth)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tie_break_test() {
+        // 初期局面の4つの着手は対称なので評価値が等しく、ビット番号が最小の手が選ばれる
+        let (myself, opponent) = parse_position(START);
+        for depth in 1..=5 {
+            assert_eq!(
+                parse_square("d3"),
+                Some(heuristic_search(myself, opponent, depth))
+            );
+            for concurrency in 1..=4 {
+                assert_eq!(
+                    parse_square("d3"),
+                    Some(heuristic_search_parallel_with(
+                        myself,
+                        opponent,
+                        depth,
+                        concurrency
+                    ))
+                );
+            }
+        }
+    }
+
+    // 窓を狭めない通常のアルファベータ法による探索（値と節点の数を返す）
+    fn alpha_beta(myself: u64, opponent: u64, depth: i32, alpha: i32, beta: i32) -> (i32, u64) {
+        let config = &EngineConfig::DEFAULT;
+        let moves = possible_moves(myself, opponent);
+        if moves == 0 {
+            return if possible_moves(opponent, myself) == 0 {
+                let v = if balance(myself, opponent) > 0 {
+                    config.confident_victory
+                } else {
+                    -config.confident_victory
+                };
+                (v, 1)
+            } else if depth == 0 {
+                (config.evaluation(myself, opponent), 1)
+            } else {
+                let (v, nodes) = alpha_beta(opponent, myself, depth - 1, -beta, -alpha);
+                (-v, nodes + 1)
+            };
+        }
+        if depth == 0 {
+            return (config.evaluation(myself, opponent), 1);
+        }
+        let mut alpha = alpha;
+        let mut nodes = 1;
+        let mut m = moves;
+        while m != 0 {
+            let mut s = 0;
+            let mut o = 0;
+            place(myself, opponent, m.trailing_zeros() as i32, &mut s, &mut o);
+            let (v, n) = alpha_beta(o, s, depth - 1, -beta, -alpha);
+            nodes += n;
+            if -v > alpha {
+                alpha = -v;
+                if alpha >= beta {
+                    break;
+                }
+            }
+            m &= m - 1;
+        }
+        (alpha, nodes)
+    }
+
+    #[test]
+    fn principal_variation_test() {
+        // 通常のアルファベータ法と同じ値と手が求まる
+        let config = &EngineConfig::DEFAULT;
+        for board in [START, MIDGAME].into_iter().chain(ENDGAMES) {
+            let (myself, opponent) = parse_position(board);
+            for depth in 1..=6 {
+                let mut ctx = SearchContext::new(config, &NEVER_STOP);
+                assert_eq!(
+                    alpha_beta(myself, opponent, depth, INTMIN, INTMAX).0,
+                    heuristic_search_sub(&mut ctx, myself, opponent, depth, 0, INTMIN, INTMAX)
+                );
+                let mut best = INTMIN;
+                let mut chosen = -1;
+                let mut m = possible_moves(myself, opponent);
+                while m != 0 {
+                    let i = m.trailing_zeros() as i32;
+                    let mut s = 0;
+                    let mut o = 0;
+                    let turns = place(myself, opponent, i, &mut s, &mut o);
+                    let v = -alpha_beta(o, s, depth - 1, INTMIN, INTMAX).0
+                        + config.openness_evaluation(myself, opponent, turns);
+                    if is_better_move(v, i, best, chosen) {
+                        best = v;
+                        chosen = i;
+                    }
+                    m &= m - 1;
+                }
+                assert_eq!(chosen, heuristic_search(myself, opponent, depth));
+                assert_eq!(
+                    chosen,
+                    heuristic_search_parallel_with(myself, opponent, depth, 3)
+                );
+            }
+        }
+        // 探索する節点の数が減る
+        let (myself, opponent) = parse_position(MIDGAME);
+        let mut ctx = SearchContext::new(config, &NEVER_STOP);
+        heuristic_search_sub(&mut ctx, myself, opponent, 8, 0, INTMIN, INTMAX);
+        let (_, nodes) = alpha_beta(myself, opponent, 8, INTMIN, INTMAX);
+        assert!(ctx.nodes < nodes, "{} / {nodes}", ctx.nodes);
+    }
+
+    #[test]
+    fn move_ordering_test() {
+        // キラームーブと history による手の順序付けで、部分探索の節点の数が減る
+        let mut random = Random::new(50);
+        let mut nodes = 0;
+        for _ in 0..8 {
+            let (myself, opponent) = loop {
+                let discs = 16 + random.below(24) as i32;
+                if let Some((myself, opponent)) = probcut::sample_position(&mut random, discs)
+                    && possible_moves(myself, opponent) != 0
+                {
+                    break (myself, opponent);
+                }
+            };
+            let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, &NEVER_STOP);
+            assert_eq!(
+                alpha_beta(myself, opponent, 6, INTMIN, INTMAX).0,
+                heuristic_search_sub(&mut ctx, myself, opponent, 6, 0, INTMIN, INTMAX)
+            );
+            nodes += ctx.nodes;
+        }
+        // 順序付けをする前は 272269 節点
+        assert!(nodes < 180_000, "{nodes} nodes");
+    }
+
+    #[test]
+    fn parallel_consistency_test() {
+        // 並列数によらず逐次版と同じ手が選ばれる
+        let max_concurrency = max(num_cpus::get() as i32, 8);
+        let (myself, opponent) = parse_position(MIDGAME);
+        let heuristic = heuristic_search(myself, opponent, 7);
+        for concurrency in 1..=max_concurrency {
+            assert_eq!(
+                heuristic,
+                heuristic_search_parallel_with(myself, opponent, 7, concurrency)
+            );
+        }
+        for board in ENDGAMES {
+            let (myself, opponent) = parse_position(board);
+            let full = full_search(myself, opponent);
+            let heuristic = heuristic_search(myself, opponent, 5);
+            for concurrency in 1..=max_concurrency {
+                assert_eq!(
+                    full,
+                    full_search_parallel_with(myself, opponent, concurrency)
+                );
+                assert_eq!(
+                    heuristic,
+                    heuristic_search_parallel_with(myself, opponent, 5, concurrency)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn random_search_test() {
+        // 同じ seed に対しては同じ手を返す
+        let (myself, opponent) = parse_position(MIDGAME);
+        for seed in 0..8 {
+            assert_eq!(
+                heuristic_search_random(myself, opponent, 5, 500, seed),
+                heuristic_search_random(myself, opponent, 5, 500, seed)
+            );
+        }
+        // 評価値の差が margin を超える手は選ばれない
+        for seed in 0..8 {
+            assert_eq!(
+                heuristic_search(myself, opponent, 5),
+                heuristic_search_random(myself, opponent, 5, 0, seed)
+            );
+        }
+        // 負の margin は 0 として扱う
+        for seed in 0..8 {
+            assert_eq!(
+                heuristic_search(myself, opponent, 5),
+                heuristic_search_random(myself, opponent, 5, -1, seed)
+            );
+            assert_eq!(
+                heuristic_search(myself, opponent, 5),
+                heuristic_search_random(myself, opponent, 5, i32::MIN, seed)
+            );
+        }
+        // 初期局面の4つの着手は評価値が等しいので、seed によって異なる手が選ばれる
+        let (myself, opponent) = parse_position(START);
+        let moves = possible_moves(myself, opponent);
+        let mut chosen = 0;
+        for seed in 0..32 {
+            let i = heuristic_search_random(myself, opponent, 3, 0, seed);
+            assert_ne!(0, moves & index_to_bit(i));
+            chosen |= index_to_bit(i);
+        }
+        assert_eq!(moves, chosen);
+        // 完全探索の段階では常に最良の手を選ぶ
+        for board in [ENDGAMES[0], ENDGAMES[3]] {
+            let (myself, opponent) = parse_position(board);
+            for seed in 0..8 {
+                assert_eq!(
+                    full_search(myself, opponent),
+                    choose_move_random(myself, opponent, 10000, seed)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn perft_test() {
+        // 初期局面からの既知の葉の数
+        let (myself, opponent) = parse_position(START);
+        let expected = [1, 4, 12, 56, 244, 1396, 8200, 55092];
+        for (depth, &nodes) in expected.iter().enumerate() {
+            assert_eq!(nodes, perft(myself, opponent, depth as i32));
+        }
+        // パスも1手として数え、終局した局面は葉になる
+        let (myself, opponent) = parse_position(&format!("XO{} X", "-".repeat(62)));
+        assert_eq!(1, perft(myself, opponent, 1));
+        assert_eq!(1, perft(myself, opponent, 2));
+        let (myself, opponent) = parse_position(&format!("{}{} X", "O".repeat(32), "X".repeat(32)));
+        assert_eq!(1, perft(myself, opponent, 3));
     }
 }
@@ -0,0 +1,735 @@
+use crate::random::Random;
+use crate::{
+    EngineConfig, NEVER_STOP, SearchContext, balance, count_bits, full_search, full_search_sub,
+    nth_move, place, possible_moves,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// モンテカルロ木探索の設定
+#[derive(Clone, Debug)]
+pub struct MctsConfig {
+    /// 全スレッドを合わせたプレイアウトの回数の上限（0 の場合は制限しない）
+    pub playouts: u64,
+    /// 探索時間の上限（None の場合は制限しない）
+    pub time_limit: Option<Duration>,
+    /// 探索に用いるスレッド数
+    pub concurrency: i32,
+    /// UCT の探索項に掛ける係数
+    pub exploration: f64,
+    /// プレイアウトで静的評価関数を用いて手を選ぶかどうか
+    pub guided_rollouts: bool,
+    /// プレイアウトで手を選ぶときに用いる静的評価関数の設定
+    pub engine: EngineConfig,
+    /// 乱数のシード値
+    pub seed: u64,
+    /// 空きマスがこの数以下の局面を完全探索で解き、探索木に勝敗を確定させる（0 の場合は確定させない）
+    ///
+    /// 根の局面の空きマスがこの数以下の場合は、木探索をせずに full_search と同じ手を返す
+    ///
+    /// 既定値は EngineConfig::DEFAULT の逐次版の探索が完全探索に切り替える空きマスの数
+    pub solver_empties: i32,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            playouts: 100000,
+            time_limit: None,
+            concurrency: 1,
+            exploration: 1.0,
+            guided_rollouts: false,
+            engine: EngineConfig::DEFAULT,
+            seed: 0,
+            // 盤上の石が full_search_occupancy より多い局面、つまり空きマスが 63 - full_search_occupancy 以下の局面で完全探索する
+            solver_empties: 63 - EngineConfig::DEFAULT.full_search_occupancy,
+        }
+    }
+}
+
+/// モンテカルロ木探索の結果
+#[derive(Clone, Debug)]
+pub struct MctsResult {
+    /// 選んだ手のビット番号（打つ手がない場合は -1）
+    pub best_move: i32,
+    /// ビット番号ごとの根の子の訪問回数
+    pub visits: [u64; 64],
+    /// ビット番号ごとの根の子の勝ち数（引き分けは 0.5 勝とする）
+    pub wins: [f64; 64],
+    /// ビット番号ごとの根の子の確定した結果（勝ち 1、引き分け 0.5、負け 0）
+    pub proven: [Option<f64>; 64],
+    /// 全スレッドを合わせたプレイアウトの回数
+    pub playouts: u64,
+}
+
+impl MctsResult {
+    /// 手 index の勝率を返す（訪問されていない場合は None）
+    pub fn win_rate(&self, index: i32) -> Option<f64> {
+        let n = self.visits[index as usize];
+        if n == 0 {
+            None
+        } else {
+            Some(self.wins[index as usize] / n as f64)
+        }
+    }
+}
+
+// 探索木のノード
+struct Node {
+    // 手番のプレイヤーから見た局面
+    myself: u64,
+    opponent: u64,
+    // このノードに至った手のビット番号（パスの場合は -1）
+    index: i32,
+    // まだ展開していない手のビットボード表現
+    untried: u64,
+    // パスによる子をまだ展開していないかどうか
+    untried_pass: bool,
+    // 子ノードの番号
+    children: Vec<usize>,
+    // 訪問回数
+    visits: u64,
+    // このノードに至る手を打ったプレイヤーから見た勝ち数
+    wins: f64,
+    // このノードに至る手を打ったプレイヤーから見た確定した結果
+    proof: Option<f64>,
+}
+
+impl Node {
+    // solver_empties が正の場合は終局した局面と空きマスが solver_empties 以下の局面の結果を確定させる
+    //
+    // 完全探索にはスレッドごとの探索の状態 ctx を使う
+    fn new(
+        ctx: &mut SearchContext,
+        myself: u64,
+        opponent: u64,
+        index: i32,
+        solver_empties: i32,
+    ) -> Node {
+        let moves = possible_moves(myself, opponent);
+        let untried_pass = moves == 0 && possible_moves(opponent, myself) != 0;
+        let mut proof = None;
+        if solver_empties > 0 {
+            if moves == 0 && !untried_pass {
+                proof = Some(1.0 - outcome(balance(myself, opponent)));
+            } else if 64 - count_bits(myself | opponent) <= solver_empties {
+                // 勝敗だけを知ればよいので最小の窓で完全探索する
+                let v = full_search_sub(ctx, myself, opponent, -1, 1);
+                proof = Some(1.0 - outcome(v));
+            }
+        }
+        Node {
+            myself,
+            opponent,
+            index,
+            untried: moves,
+            untried_pass,
+            children: Vec::new(),
+            visits: 0,
+            wins: 0.0,
+            proof,
+        }
+    }
+
+    // すべての手を展開済みかどうかを返す
+    fn is_expanded(&self) -> bool {
+        self.untried == 0 && !self.untried_pass
+    }
+}
+
+// 石数の差から結果（勝ち 1、引き分け 0.5、負け 0）を返す
+fn outcome(b: i32) -> f64 {
+    if b > 0 {
+        1.0
+    } else if b < 0 {
+        0.0
+    } else {
+        0.5
+    }
+}
+
+// 1つのスレッドが持つ探索木
+struct Tree {
+    nodes: Vec<Node>,
+    // 局面の勝敗を確定させる完全探索の状態（置換表を探索木の全体で共有する）
+    ctx: SearchContext<'static>,
+    random: Random,
+    exploration: f64,
+    guided_rollouts: bool,
+    engine: EngineConfig,
+    solver_empties: i32,
+}
+
+impl Tree {
+    fn new(myself: u64, opponent: u64, config: &MctsConfig, seed: u64) -> Tree {
+        let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, &NEVER_STOP);
+        Tree {
+            nodes: vec![Node::new(&mut ctx, myself, opponent, -1, 0)],
+            ctx,
+            random: Random::new(seed),
+            exploration: config.exploration,
+            guided_rollouts: config.guided_rollouts,
+            engine: config.engine,
+            solver_empties: config.solver_empties,
+        }
+    }
+
+    // 選択、展開、プレイアウト、逆伝播を1回ずつ行う
+    fn iterate(&mut self) {
+        let mut path = vec![0];
+        let mut current = 0;
+        loop {
+            let node = &self.nodes[current];
+            if node.proof.is_some() {
+                break;
+            }
+            if !node.is_expanded() {
+                current = self.expand(current);
+                path.push(current);
+                break;
+            }
+            if node.children.is_empty() {
+                break;
+            }
+            current = self.select(current);
+            path.push(current);
+        }
+        // r は各ノードで手番のプレイヤーから見た結果
+        let node = &self.nodes[current];
+        let mut r = match node.proof {
+            Some(p) => 1.0 - p,
+            None => self.rollout(node.myself, node.opponent),
+        };
+        for &k in path.iter().rev() {
+            let node = &mut self.nodes[k];
+            node.visits += 1;
+            node.wins += 1.0 - r;
+            r = 1.0 - r;
+            if self.solver_empties > 0 {
+                self.prove(k);
+            }
+        }
+    }
+
+    // 子ノードの確定した結果から、このノードの結果を確定できれば確定させる
+    fn prove(&mut self, parent: usize) {
+        let node = &self.nodes[parent];
+        if node.proof.is_some() || node.children.is_empty() {
+            return;
+        }
+        // 手番のプレイヤーから見た、確定した子の結果の最大値
+        let mut best: Option<f64> = None;
+        let mut all_proven = node.is_expanded();
+        for &k in &node.children {
+            match self.nodes[k].proof {
+                Some(p) => best = Some(best.map_or(p, |b: f64| b.max(p))),
+                None => all_proven = false,
+            }
+        }
+        let proof = match best {
+            // 勝ちが確定する手があれば、このノードは手番のプレイヤーの勝ちで確定する
+            Some(1.0) => Some(0.0),
+            Some(b) if all_proven => Some(1.0 - b),
+            _ => None,
+        };
+        self.nodes[parent].proof = proof;
+    }
+
+    // 未展開の手を1つ無作為に選んで子ノードを作り、その番号を返す
+    fn expand(&mut self, parent: usize) -> usize {
+        let node = &mut self.nodes[parent];
+        let (myself, opponent) = (node.myself, node.opponent);
+        let child = if node.untried_pass {
+            node.untried_pass = false;
+            Node::new(&mut self.ctx, opponent, myself, -1, self.solver_empties)
+        } else {
+            let n = self.random.below(count_bits(node.untried) as u64) as i32;
+            let i = nth_move(node.untried, n);
+            node.untried &= !(1 << i);
+            let mut s: u64 = 0;
+            let mut o: u64 = 0;
+            place(myself, opponent, i, &mut s, &mut o);
+            Node::new(&mut self.ctx, o, s, i, self.solver_empties)
+        };
+        let k = self.nodes.len();
+        self.nodes.push(child);
+        self.nodes[parent].children.push(k);
+        k
+    }
+
+    // UCT の値が最大の子ノードの番号を返す
+    fn select(&self, parent: usize) -> usize {
+        let node = &self.nodes[parent];
+        let log_n = (node.visits as f64).ln();
+        let mut best = node.children[0];
+        let mut best_value = f64::NEG_INFINITY;
+        for &k in &node.children {
+            let child = &self.nodes[k];
+            let n = child.visits as f64;
+            // 負けが確定した手は、他に選べる手がない場合を除いて選ばない
+            let value = if child.proof == Some(0.0) {
+                f64::MIN
+            } else {
+                child.wins / n + self.exploration * (log_n / n).sqrt()
+            };
+            if value > best_value {
+                best_value = value;
+                best = k;
+            }
+        }
+        best
+    }
+
+    // 終局まで打ち進め、手番のプレイヤーから見た結果（勝ち 1、引き分け 0.5、負け 0）を返す
+    fn rollout(&mut self, myself: u64, opponent: u64) -> f64 {
+        let (mut myself, mut opponent) = (myself, opponent);
+        let mut sign = 1;
+        let mut passed = false;
+        loop {
+            let moves = possible_moves(myself, opponent);
+            if moves == 0 {
+                if passed {
+                    break;
+                }
+                passed = true;
+            } else {
+                passed = false;
+                let i = self.rollout_move(myself, opponent, moves);
+                let mut s: u64 = 0;
+                let mut o: u64 = 0;
+                place(myself, opponent, i, &mut s, &mut o);
+                myself = s;
+                opponent = o;
+            }
+            (myself, opponent) = (opponent, myself);
+            sign = -sign;
+        }
+        outcome(sign * balance(myself, opponent))
+    }
+
+    // プレイアウトで打つ手を選ぶ
+    //
+    // 静的評価関数を用いる場合は、半分の確率で評価値が最良の手を選ぶ
+    fn rollout_move(&mut self, myself: u64, opponent: u64, moves: u64) -> i32 {
+        if self.guided_rollouts && self.random.below(2) == 0 {
+            let mut best = -1;
+            let mut best_value = i32::MIN;
+            let mut m = moves;
+            while m != 0 {
+                let i = m.trailing_zeros() as i32;
+                m &= m - 1;
+                let mut s: u64 = 0;
+                let mut o: u64 = 0;
+                place(myself, opponent, i, &mut s, &mut o);
+                let v = -self.engine.evaluation(o, s);
+                if v > best_value {
+                    best_value = v;
+                    best = i;
+                }
+            }
+            best
+        } else {
+            let n = self.random.below(count_bits(moves) as u64) as i32;
+            nth_move(moves, n)
+        }
+    }
+}
+
+/// UCT に基づくモンテカルロ木探索をし、根の子ごとの訪問回数と最も訪問された手を返す
+///
+/// 各スレッドは独立した探索木を作り、最後に根の子の訪問回数と勝ち数を合算する（根並列化）
+///
+/// 訪問回数が等しい手が複数ある場合は、ビット番号が最小の手を選ぶ
+///
+/// solver_empties が正の場合は勝敗が確定した手を優先し、負けが確定した手を避ける
+pub fn mcts_search(myself: u64, opponent: u64, config: &MctsConfig) -> MctsResult {
+    let mut result = MctsResult {
+        best_move: -1,
+        visits: [0; 64],
+        wins: [0.0; 64],
+        proven: [None; 64],
+        playouts: 0,
+    };
+    if possible_moves(myself, opponent) == 0 {
+        return result;
+    }
+    // 根から完全探索できる場合は choose_move と同じく最良の手を選ぶ
+    if config.solver_empties > 0 && 64 - count_bits(myself | opponent) <= config.solver_empties {
+        result.best_move = full_search(myself, opponent);
+        return result;
+    }
+    let concurrency = config.concurrency.max(1) as u64;
+    let start = Instant::now();
+    let trees: Vec<Tree> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..concurrency)
+            .map(|t| {
+                // プレイアウトの回数をスレッド間で分ける
+                let playouts = if config.playouts == 0 {
+                    u64::MAX
+                } else {
+                    config.playouts / concurrency + (t < config.playouts % concurrency) as u64
+                };
+                let seed = config
+                    .seed
+                    .wrapping_add(t.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                scope.spawn(move || {
+                    let mut tree = Tree::new(myself, opponent, config, seed);
+                    let mut k = 0;
+                    // 根の結果が確定すればそれ以上探索しない
+                    while k < playouts && tree.nodes[0].proof.is_none() {
+                        // 時間の確認は一定回数ごとに行う
+                        if k % 64 == 0
+                            && config
+                                .time_limit
+                                .is_some_and(|limit| start.elapsed() >= limit)
+                        {
+                            break;
+                        }
+                        tree.iterate();
+                        k += 1;
+                    }
+                    tree
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for tree in &trees {
+        let root = &tree.nodes[0];
+        result.playouts += root.visits;
+        for &k in &root.children {
+            let child = &tree.nodes[k];
+            result.visits[child.index as usize] += child.visits;
+            result.wins[child.index as usize] += child.wins;
+            if child.proof.is_some() {
+                result.proven[child.index as usize] = child.proof;
+            }
+        }
+    }
+    // 確定した結果が良い手を優先し、その中で訪問回数が最大の手を選ぶ
+    let rank = |i: usize| match result.proven[i] {
+        Some(1.0) => 2,
+        Some(0.0) => 0,
+        _ => 1,
+    };
+    let mut m = possible_moves(myself, opponent);
+    while m != 0 {
+        let i = m.trailing_zeros() as usize;
+        m &= m - 1;
+        let best = result.best_move as usize;
+        if result.best_move < 0
+            || rank(i) > rank(best)
+            || (rank(i) == rank(best) && result.visits[i] > result.visits[best])
+        {
+            result.best_move = i as i32;
+        }
+    }
+    result
+}
+
+/// UCT に基づくモンテカルロ木探索をし、最も訪問された手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// mcts_choose_move_with_solver で完全探索を用いない場合と同じ
+///
+/// # Safety
+///
+/// visits は NULL か、書き込み可能な長さ 64 の配列を指す必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mcts_choose_move(
+    myself: u64,
+    opponent: u64,
+    playouts: u64,
+    millis: u64,
+    concurrency: i32,
+    guided: i32,
+    seed: u64,
+    visits: *mut u64,
+) -> i32 {
+    unsafe {
+        mcts_choose_move_with_solver(
+            myself,
+            opponent,
+            playouts,
+            millis,
+            concurrency,
+            guided,
+            0,
+            seed,
+            visits,
+        )
+    }
+}
+
+/// UCT に基づくモンテカルロ木探索をし、最も訪問された手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// playouts はプレイアウトの回数の上限、millis はミリ秒単位の探索時間の上限で、それぞれ 0 の場合は制限しない（両方 0 の場合は既定の回数で探索する）
+///
+/// guided が 0 でない場合はプレイアウトで静的評価関数を用いる
+///
+/// solver_empties が正の場合は、空きマスがその数以下の局面を完全探索で解いて勝敗を確定させる（根の局面で空きマスがその数以下の場合は full_search と同じ手を返す）
+///
+/// visits が NULL でない場合は、ビット番号ごとの根の子の訪問回数を長さ 64 の配列に書き込む
+///
+/// # Safety
+///
+/// visits は NULL か、書き込み可能な長さ 64 の配列を指す必要がある
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn mcts_choose_move_with_solver(
+    myself: u64,
+    opponent: u64,
+    playouts: u64,
+    millis: u64,
+    concurrency: i32,
+    guided: i32,
+    solver_empties: i32,
+    seed: u64,
+    visits: *mut u64,
+) -> i32 {
+    let mut config = MctsConfig {
+        playouts,
+        time_limit: None,
+        concurrency,
+        guided_rollouts: guided != 0,
+        seed,
+        solver_empties,
+        ..MctsConfig::default()
+    };
+    if millis > 0 {
+        config.time_limit = Some(Duration::from_millis(millis));
+    } else if playouts == 0 {
+        config.playouts = MctsConfig::default().playouts;
+    }
+    let result = mcts_search(myself, opponent, &config);
+    if !visits.is_null() {
+        let visits = unsafe { std::slice::from_raw_parts_mut(visits, 64) };
+        visits.copy_from_slice(&result.visits);
+    }
+    result.best_move
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::full_search;
+    use crate::game::{Game, parse_position};
+
+    // 序盤の局面
+    const MIDGAME: &str = "
+        --X--O--
+        ---XOO--
+        --OOXOO-
+        -OOOOOO-
+        ---OOO--
+        ----O---
+        --------
+        -------- X";
+
+    #[test]
+    fn mcts_visit_test() {
+        // 訪問回数の合計はプレイアウトの回数に等しく、合法手以外は訪問されない
+        let (myself, opponent) = parse_position(MIDGAME);
+        let config = MctsConfig {
+            playouts: 2000,
+            concurrency: 2,
+            ..MctsConfig::default()
+        };
+        let result = mcts_search(myself, opponent, &config);
+        assert_eq!(2000, result.playouts);
+        assert_eq!(2000, result.visits.iter().sum::<u64>());
+        let moves = possible_moves(myself, opponent);
+        for i in 0..64 {
+            if moves & (1 << i) == 0 {
+                assert_eq!(0, result.visits[i]);
+            }
+        }
+        assert_ne!(0, moves & (1 << result.best_move));
+        // 同じシード値とプレイアウトの回数に対しては同じ結果になる
+        let again = mcts_search(myself, opponent, &config);
+        assert_eq!(result.visits, again.visits);
+        // 打つ手がないとき
+        let (myself, opponent) = parse_position(
+            "
+            OXOOOOOO
+            OXXXXOXX
+            OXOOOOX-
+            OXOOOOX-
+            OXXOOOXO
+            OXXXXXX-
+            --------
+            -------- X",
+        );
+        let result = mcts_search(myself, opponent, &config);
+        assert_eq!(-1, result.best_move);
+    }
+
+    #[test]
+    fn mcts_endgame_test() {
+        // 終盤では完全探索と同じ手を選ぶ
+        let positions = [
+            "
+            XXOOXXXO
+            OOOOOXXO
+            OOXOOOXO
+            OOXXOXOO
+            OOOOXOOO
+            OOOXOOOO
+            O-OOOOOO
+            --OOOOXO X",
+            "
+            --OXXOOO
+            ---XXXOO
+            OOOOXOXO
+            OOOXXOOO
+            OOOXXXXO
+            -OOXXXOO
+            --XOOOOO
+            -XXXXXXX X",
+        ];
+        for board in positions {
+            let (myself, opponent) = parse_position(board);
+            for guided_rollouts in [false, true] {
+                let config = MctsConfig {
+                    playouts: 20000,
+                    guided_rollouts,
+                    solver_empties: 0,
+                    ..MctsConfig::default()
+                };
+                assert_eq!(
+                    full_search(myself, opponent),
+                    mcts_search(myself, opponent, &config).best_move
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mcts_time_limit_test() {
+        let config = MctsConfig {
+            playouts: 0,
+            time_limit: Some(Duration::from_millis(100)),
+            concurrency: 2,
+            ..MctsConfig::default()
+        };
+        let game = Game::new();
+        let (myself, opponent) = (game.myself(), game.opponent());
+        let start = Instant::now();
+        let result = mcts_search(myself, opponent, &config);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(result.playouts > 0);
+        let mut visits = [0u64; 64];
+        let i = unsafe { mcts_choose_move(myself, opponent, 500, 0, 1, 1, 0, visits.as_mut_ptr()) };
+        assert_eq!(500, visits.iter().sum::<u64>());
+        assert!(visits[i as usize] > 0);
+    }
+
+    // 評価の安定した手を数手打ち進めた局面を返す
+    fn advance(myself: u64, opponent: u64, plies: i32) -> (u64, u64) {
+        let (mut myself, mut opponent) = (myself, opponent);
+        for _ in 0..plies {
+            let i = crate::heuristic_search(myself, opponent, 1);
+            if i >= 0 {
+                let mut s: u64 = 0;
+                let mut o: u64 = 0;
+                place(myself, opponent, i, &mut s, &mut o);
+                myself = s;
+                opponent = o;
+            }
+            (myself, opponent) = (opponent, myself);
+        }
+        (myself, opponent)
+    }
+
+    #[test]
+    fn mcts_solver_test() {
+        let (myself, opponent) = parse_position(
+            "
+            --O-O---
+            XXOOXXXX
+            XXOOOOX-
+            -XXOOXOO
+            XOXXOOO-
+            OXXXOOXX
+            -XX-O-XO
+            -------- X",
+        );
+        let (myself, opponent) = advance(myself, opponent, 6);
+        assert_eq!(14, 64 - count_bits(myself | opponent));
+        let config = MctsConfig {
+            playouts: 3000,
+            solver_empties: 10,
+            ..MctsConfig::default()
+        };
+        let result = mcts_search(myself, opponent, &config);
+        // 確定した結果は完全探索の結果と一致し、最良の結果の手を選ぶ
+        let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, &NEVER_STOP);
+        let mut best = 0.0;
+        let mut m = possible_moves(myself, opponent);
+        let mut outcomes = [0.0; 64];
+        while m != 0 {
+            let i = m.trailing_zeros() as i32;
+            m &= m - 1;
+            let mut s: u64 = 0;
+            let mut o: u64 = 0;
+            place(myself, opponent, i, &mut s, &mut o);
+            let v = outcome(-full_search_sub(&mut ctx, o, s, -64, 64));
+            outcomes[i as usize] = v;
+            best = f64::max(best, v);
+            if let Some(p) = result.proven[i as usize] {
+                assert_eq!(v, p);
+            }
+        }
+        assert!(result.proven.iter().any(|p| p.is_some()));
+        assert_eq!(best, outcomes[result.best_move as usize]);
+        // 根から完全探索できる場合は full_search と同じ手を選ぶ
+        let config = MctsConfig {
+            playouts: 100,
+            solver_empties: 14,
+            ..MctsConfig::default()
+        };
+        assert_eq!(
+            full_search(myself, opponent),
+            mcts_search(myself, opponent, &config).best_move
+        );
+    }
+
+    #[test]
+    fn mcts_default_solver_test() {
+        // 既定の設定では、EngineConfig::DEFAULT で完全探索する局面の手は完全探索の結果に達する
+        let config = MctsConfig::default();
+        assert_eq!(
+            EngineConfig::DEFAULT.search_depth(0xFFFF_FFFF_FFFF_E000, 0),
+            None
+        );
+        assert!(
+            EngineConfig::DEFAULT
+                .search_depth(0xFFFF_FFFF_FFFF_C000, 0)
+                .is_some()
+        );
+        assert_eq!(13, config.solver_empties);
+        let (myself, opponent) = parse_position(
+            "
+            --O-O---
+            XXOOXXXX
+            XXOOOOX-
+            -XXOOXOO
+            XOXXOOO-
+            OXXXOOXX
+            -XX-O-XO
+            -------- X",
+        );
+        let (myself, opponent) = advance(myself, opponent, 8);
+        assert_eq!(12, 64 - count_bits(myself | opponent));
+        let stats = crate::full_search_stats(myself, opponent);
+        let i = mcts_search(myself, opponent, &config).best_move;
+        let mut s: u64 = 0;
+        let mut o: u64 = 0;
+        place(myself, opponent, i, &mut s, &mut o);
+        let mut ctx = SearchContext::new(&EngineConfig::DEFAULT, &NEVER_STOP);
+        assert_eq!(stats.score, -full_search_sub(&mut ctx, o, s, -64, 64));
+    }
+}
@@ -0,0 +1,262 @@
+use crate::game::{Game, parse_board, parse_square, square_name};
+use crate::{
+    EngineConfig, analyze, choose_move_parallel_with, count_bits, full_search_parallel_with,
+    heuristic_search_parallel_with,
+};
+
+/// NBoard プロトコルで GUI と通信するエンジン
+///
+/// GUI からのコマンドを1行ずつ受け取り、応答の行を返す
+pub struct NBoardEngine {
+    game: Game,
+    depth: Option<i32>,
+    concurrency: i32,
+}
+
+impl NBoardEngine {
+    /// エンジンの名前
+    pub const NAME: &'static str = "ReversiAI";
+
+    /// concurrency 個のスレッドで探索するエンジンを返す
+    pub fn new(concurrency: i32) -> NBoardEngine {
+        NBoardEngine {
+            game: Game::new(),
+            depth: None,
+            concurrency,
+        }
+    }
+
+    /// 現在の対局を返す
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// コマンドを1行処理して応答の行を返す
+    ///
+    /// quit コマンドの場合は None を返す
+    pub fn handle(&mut self, line: &str) -> Option<Vec<String>> {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        let response = match command {
+            "nboard" => vec![format!("set myname {}", NBoardEngine::NAME)],
+            "set" => self.set(args),
+            "move" => match self.play(args) {
+                Ok(()) => vec![],
+                Err(message) => vec![format!("status {message}")],
+            },
+            "hint" => self.hint(args.parse().unwrap_or(1)),
+            "go" => vec![format!("=== {}", self.go())],
+            "ping" => vec![format!("pong {args}")],
+            "learn" => vec!["learned".to_string()],
+            "quit" => return None,
+            // 対応しないコマンドは無視する
+            _ => vec![],
+        };
+        Some(response)
+    }
+
+    // set コマンドを処理する
+    fn set(&mut self, args: &str) -> Vec<String> {
+        let (name, value) = args.split_once(' ').unwrap_or((args, ""));
+        match name {
+            "depth" => {
+                self.depth = value.trim().parse().ok().filter(|&d| d > 0);
+                vec![]
+            }
+            "game" => match parse_ggf(value) {
+                Some(game) => {
+                    self.game = game;
+                    vec![]
+                }
+                None => vec!["status invalid game".to_string()],
+            },
+            // contempt などの設定は無視する
+            _ => vec![],
+        }
+    }
+
+    // move コマンドの手を打つ
+    fn play(&mut self, args: &str) -> Result<(), String> {
+        let text = args.split('/').next().unwrap_or("");
+        play_move(&mut self.game, text).ok_or_else(|| format!("illegal move {text}"))
+    }
+
+    // 手番のプレイヤーが打つ手の表記を返す
+    fn go(&self) -> String {
+        let myself = self.game.myself();
+        let opponent = self.game.opponent();
+        if self.game.legal_moves() == 0 {
+            return "PA".to_string();
+        }
+        let empties = 64 - count_bits(myself | opponent);
+        let index = match self.depth {
+            Some(depth) if depth >= empties => {
+                full_search_parallel_with(myself, opponent, self.concurrency)
+            }
+            Some(depth) => {
+                heuristic_search_parallel_with(myself, opponent, depth, self.concurrency)
+            }
+            None => choose_move_parallel_with(myself, opponent, self.concurrency),
+        };
+        square_name(index).to_uppercase()
+    }
+
+    // 評価値の高い n 個の手について search 行を返す
+    fn hint(&self, n: usize) -> Vec<String> {
+        let myself = self.game.myself();
+        let opponent = self.game.opponent();
+        let mut lines = vec!["status analyzing".to_string()];
+        if self.game.legal_moves() == 0 {
+            lines.push("status".to_string());
+            return lines;
+        }
+        let empties = 64 - count_bits(myself | opponent);
+        let depth = self
+            .depth
+            .or_else(|| EngineConfig::DEFAULT.parallel_search_depth(myself, opponent))
+            .unwrap_or(empties);
+        for score in analyze(myself, opponent, depth, self.concurrency)
+            .iter()
+            .take(n)
+        {
+            // 部分探索の評価値は石数の差に近い尺度に縮めて報告する
+            let (eval, depth) = if score.exact {
+                (score.score as f64, empties)
+            } else {
+                ((score.score as f64 / 1000.0).clamp(-64.0, 64.0), depth)
+            };
+            lines.push(format!(
+                "search {} {:.2} 0 {}",
+                square_name(score.index).to_uppercase(),
+                eval,
+                depth
+            ));
+        }
+        lines.push("status".to_string());
+        lines
+    }
+}
+
+// 手の表記 text（"F5" や "PA" など）に従って手番のプレイヤーが打ち、打てたかを返す
+fn play_move(game: &mut Game, text: &str) -> Option<()> {
+    if text.eq_ignore_ascii_case("pa") || text.eq_ignore_ascii_case("pass") {
+        game.pass().ok()
+    } else {
+        game.play(parse_square(text)?).ok()
+    }
+}
+
+/// GGF 形式の棋譜を読み込み、最後の手まで進めた対局を返す
+///
+/// 棋譜として解釈できない場合や不正な手がある場合は None を返す
+pub fn parse_ggf(text: &str) -> Option<Game> {
+    let mut game = None;
+    for (name, value) in ggf_properties(text)? {
+        match name.as_str() {
+            "BO" => game = Some(parse_ggf_board(&value)?),
+            "B" | "W" => {
+                let game = game.as_mut()?;
+                // パスが省略されている場合は補う
+                if game.black_to_move() != (name == "B") {
+                    game.pass().ok()?;
+                }
+                play_move(game, value.split('/').next()?)?;
+            }
+            _ => (),
+        }
+    }
+    game
+}
+
+// GGF 形式の棋譜からプロパティの名前と値の組を取り出す
+fn ggf_properties(text: &str) -> Option<Vec<(String, String)>> {
+    let mut properties = Vec::new();
+    let mut chars = text.chars();
+    let mut name = String::new();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_uppercase() {
+            name.push(c);
+        } else if c == '[' {
+            let mut value = String::new();
+            loop {
+                match chars.next()? {
+                    ']' => break,
+                    c => value.push(c),
+                }
+            }
+            properties.push((std::mem::take(&mut name), value));
+        } else {
+            name.clear();
+        }
+    }
+    Some(properties)
+}
+
+// GGF 形式の BO プロパティの値（"8 <64 マス> <手番>"）から対局を作る
+fn parse_ggf_board(value: &str) -> Option<Game> {
+    parse_board(value.trim_start().strip_prefix("8 ")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "(;GM[Othello]PC[NBoard]DT[2025-01-01 00:00:00 GMT]PB[a]PW[b]RE[?]TI[5:00]TY[8]BO[8 ---------------------------O*------*O--------------------------- *];)";
+
+    #[test]
+    fn ggf_test() {
+        assert_eq!(Some(Game::new()), parse_ggf(START));
+        let game = parse_ggf(&START.replace(";)", "B[F5//1.00]W[d6];)")).unwrap();
+        assert_eq!(2, game.ply());
+        assert!(game.black_to_move());
+        assert_eq!(None, parse_ggf(&START.replace(";)", "B[A1];)")));
+        assert_eq!(None, parse_ggf("(;GM[Othello];)"));
+    }
+
+    #[test]
+    fn session_test() {
+        let mut engine = NBoardEngine::new(2);
+        assert_eq!(
+            Some(vec!["set myname ReversiAI".to_string()]),
+            engine.handle("nboard 2")
+        );
+        assert_eq!(Some(vec![]), engine.handle("set depth 3"));
+        assert_eq!(Some(vec![]), engine.handle(&format!("set game {START}")));
+        assert_eq!(Some(vec![]), engine.handle("move F5/0.00/1.2"));
+        assert_eq!(
+            Some(vec!["status illegal move A1".to_string()]),
+            engine.handle("move A1")
+        );
+        assert_eq!(Some(vec!["pong 7".to_string()]), engine.handle("ping 7"));
+        // 手番は白で、打てる手は3つある
+        let hint = engine.handle("hint 5").unwrap();
+        assert_eq!("status analyzing", hint[0]);
+        assert_eq!(5, hint.len());
+        assert!(
+            hint[1..4]
+                .iter()
+                .all(|l| l.starts_with("search ") && l.ends_with(" 0 3"))
+        );
+        assert_eq!("status", hint[4]);
+        let go = engine.handle("go").unwrap();
+        assert_eq!(1, go.len());
+        let best = hint[1].split(' ').nth(1).unwrap();
+        assert_eq!(format!("=== {best}"), go[0]);
+        assert_eq!(Some(vec!["learned".to_string()]), engine.handle("learn"));
+        assert_eq!(Some(vec![]), engine.handle("set contempt 0"));
+        assert_eq!(None, engine.handle("quit"));
+    }
+
+    #[test]
+    fn endgame_session_test() {
+        // 完全探索の範囲では石数の差を報告する
+        let mut engine = NBoardEngine::new(1);
+        let game = "(;GM[Othello]BO[8 **OO***OOOOOO**OOO*OOO*OOO**O*OOOOOO*OOOOOO*OOOOO-OOOOOO--OOOO*O *];)";
+        assert_eq!(Some(vec![]), engine.handle(&format!("set game {game}")));
+        let hint = engine.handle("hint 1").unwrap();
+        assert_eq!(3, hint.len());
+        assert!(hint[1].ends_with(" 0 3"));
+        assert_eq!(Some(vec!["=== A8".to_string()]), engine.handle("go"));
+    }
+}
@@ -0,0 +1,97 @@
+use std::cmp::Reverse;
+
+// 部分探索で手を調べる順序を決めるキラームーブと history の表
+//
+// どちらも探索の根からの手数（パスを含む）で引くので、パスの後も手番の側を取り違えない
+pub(crate) struct MoveOrdering {
+    // 根からの手数ごとの、最近 beta 以上の値で打ち切りを起こした2つの手のビット番号（なければ -1）
+    killers: [[i32; 2]; MoveOrdering::MAX_PLY],
+    // 根からの手数の偶奇と手のビット番号ごとの、打ち切りを起こした節点の残りの深さの2乗の和
+    //
+    // 手数の偶奇が等しい節点では同じプレイヤーの手番になる
+    history: [[u64; 64]; 2],
+}
+
+impl MoveOrdering {
+    // キラームーブを記録する根からの手数の上限
+    const MAX_PLY: usize = 64;
+
+    // 空の表を作る
+    pub(crate) fn new() -> Self {
+        MoveOrdering {
+            killers: [[-1; 2]; MoveOrdering::MAX_PLY],
+            history: [[0; 64]; 2],
+        }
+    }
+
+    // 根からの手数が ply の節点で打てる手 moves を、キラームーブ、history の値が大きい手、ビット番号が小さい手の順に並べた配列とその長さを返す
+    pub(crate) fn order(&self, moves: u64, ply: usize) -> ([i32; 64], usize) {
+        let killers = self.killers.get(ply).copied().unwrap_or([-1; 2]);
+        let mut keys = [(Reverse(0), 0); 64];
+        let mut len = 0;
+        let mut m = moves;
+        while m != 0 {
+            let i = m.trailing_zeros() as i32;
+            let score = if i == killers[0] {
+                u64::MAX
+            } else if i == killers[1] {
+                u64::MAX - 1
+            } else {
+                self.history[ply % 2][i as usize]
+            };
+            keys[len] = (Reverse(score), i);
+            len += 1;
+            m &= m - 1;
+        }
+        keys[..len].sort_unstable();
+        let mut order = [0; 64];
+        for (k, &(_, i)) in keys[..len].iter().enumerate() {
+            order[k] = i;
+        }
+        (order, len)
+    }
+
+    // 根からの手数が ply で残りの深さが depth の節点で手 index が打ち切りを起こしたことを記録する
+    pub(crate) fn cutoff(&mut self, index: i32, ply: usize, depth: i32) {
+        if let Some(killers) = self.killers.get_mut(ply)
+            && killers[0] != index
+        {
+            *killers = [index, killers[0]];
+        }
+        self.history[ply % 2][index as usize] += (depth * depth) as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordering_test() {
+        let mut ordering = MoveOrdering::new();
+        let moves = 0b1011_0110;
+        // 記録がなければビット番号の順
+        assert_eq!([1, 2, 4, 5, 7], ordering.order(moves, 3).0[..5]);
+        assert_eq!(5, ordering.order(moves, 3).1);
+        // history の値が大きい手が先になる
+        ordering.cutoff(5, 1, 6);
+        ordering.cutoff(4, 5, 2);
+        assert_eq!([5, 4, 1, 2, 7], ordering.order(moves, 3).0[..5]);
+        // history は手数の偶奇が等しい節点で共有する
+        assert_eq!([1, 2, 4, 5, 7], ordering.order(moves, 2).0[..5]);
+        // 同じ手数のキラームーブは、新しいものから先に調べる
+        ordering.cutoff(7, 3, 1);
+        ordering.cutoff(2, 3, 1);
+        assert_eq!([2, 7, 5, 4, 1], ordering.order(moves, 3).0[..5]);
+        ordering.cutoff(2, 3, 1);
+        assert_eq!([2, 7, 5, 4, 1], ordering.order(moves, 3).0[..5]);
+        // 打てない手のキラームーブは無視する
+        assert_eq!([2, 5, 4, 1], ordering.order(0b0011_0110, 3).0[..4]);
+        // キラームーブは手数ごとに記録する
+        assert_eq!([4, 5, 2, 7, 1], ordering.order(moves, 5).0[..5]);
+        assert_eq!([5, 4, 2, 7, 1], ordering.order(moves, 7).0[..5]);
+        // 根から遠すぎる節点でも history は使う
+        ordering.cutoff(1, 101, 100);
+        assert_eq!([1, 5, 4, 2, 7], ordering.order(moves, 101).0[..5]);
+    }
+}
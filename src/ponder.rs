@@ -0,0 +1,322 @@
+use crate::{
+    EngineConfig, NEVER_STOP, SearchShared, StopSignal, choose_move_parallel_sub, place,
+    possible_moves,
+};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// 相手の手番の間に、相手の手を予想してその手を打った後の局面を裏で探索する先読み
+///
+/// 実際の相手の手が予想と一致した場合はその探索を続けて結果を使い、一致しなかった場合は探索を捨てて改めて探索する
+///
+/// 探索には choose_move_parallel_with と同じ並列探索を用いるので、予想が当たった場合の結果は choose_move_parallel_with と等しい
+pub struct Ponder {
+    // 相手が打つ前の局面
+    myself: u64,
+    opponent: u64,
+    // 予想した相手の手（パスの場合は -1）
+    predicted: i32,
+    // 並列数
+    concurrency: i32,
+    // 探索の設定
+    config: EngineConfig,
+    // 裏で動いている探索の中断要求
+    stop: Arc<StopSignal>,
+    // 裏で動いている探索のスレッド
+    handle: Option<JoinHandle<i32>>,
+}
+
+impl Ponder {
+    // 相手の手を予想するときの先読みの深さ
+    const PREDICTION_DEPTH: i32 = 3;
+
+    /// 相手の手番の局面で先読みを始める
+    ///
+    /// myself は自分の石、opponent は次に打つ相手の石を表す
+    ///
+    /// predicted は予想する相手の手のビット番号で、負の値の場合は浅い探索によって予想する
+    ///
+    /// 相手が打てない場合はパスを予想する
+    ///
+    /// concurrency は探索の並列数
+    pub fn start(myself: u64, opponent: u64, predicted: i32, concurrency: i32) -> Ponder {
+        Ponder::start_with_config(
+            EngineConfig::DEFAULT,
+            myself,
+            opponent,
+            predicted,
+            concurrency,
+        )
+    }
+
+    /// 設定 config に従って start と同じ先読みを始める
+    pub fn start_with_config(
+        config: EngineConfig,
+        myself: u64,
+        opponent: u64,
+        predicted: i32,
+        concurrency: i32,
+    ) -> Ponder {
+        let predicted = if possible_moves(opponent, myself) == 0 {
+            -1
+        } else if predicted < 0 {
+            config.heuristic_search(opponent, myself, Ponder::PREDICTION_DEPTH)
+        } else {
+            predicted
+        };
+        let stop = Arc::new(StopSignal::new());
+        let (s, o) = after_reply(myself, opponent, predicted);
+        let handle = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                let shared = SearchShared {
+                    stop: &stop,
+                    tables: None,
+                };
+                choose_move_parallel_sub(&config, s, o, concurrency, &shared)
+            })
+        };
+        Ponder {
+            myself,
+            opponent,
+            predicted,
+            concurrency,
+            config,
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// 予想した相手の手のビット番号を返す（パスの場合は -1）
+    pub fn predicted_move(&self) -> i32 {
+        self.predicted
+    }
+
+    /// 裏で動いている探索が終わっているかを返す
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_none_or(|h| h.is_finished())
+    }
+
+    /// 実際の相手の手 actual（パスの場合は -1）を受け取り、その後の局面での自分の手のビット番号を返す
+    ///
+    /// 予想が当たった場合は先読みの探索の終了を待ってその結果を返し、外れた場合は先読みを捨てて改めて探索する
+    ///
+    /// 打つ手がない場合は -1 を返す
+    ///
+    /// actual が相手の打てる手でない場合や、相手が打てるのに -1 の場合は、先読みを捨てて -1 を返す
+    pub fn resolve(mut self, actual: i32) -> i32 {
+        let handle = self.handle.take().unwrap();
+        if !is_legal_reply(self.myself, self.opponent, actual) {
+            self.stop.request();
+            handle.join().unwrap();
+            -1
+        } else if actual == self.predicted {
+            handle.join().unwrap()
+        } else {
+            self.stop.request();
+            handle.join().unwrap();
+            let (s, o) = after_reply(self.myself, self.opponent, actual);
+            choose_move_parallel_sub(&self.config, s, o, self.concurrency, &NEVER_STOP)
+        }
+    }
+}
+
+impl Drop for Ponder {
+    // 裏で動いている探索を中断して終了を待つ
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.stop.request();
+            handle.join().unwrap();
+        }
+    }
+}
+
+// reply が相手の打てる手か、相手が打てない場合のパス（-1）であるかを返す
+fn is_legal_reply(myself: u64, opponent: u64, reply: i32) -> bool {
+    let moves = possible_moves(opponent, myself);
+    if reply == -1 {
+        moves == 0
+    } else {
+        (0..64).contains(&reply) && moves & (1 << reply) != 0
+    }
+}
+
+// 相手が reply を打った後の局面を自分から見た形で返す（reply は相手の打てる手かパスである必要がある）
+fn after_reply(myself: u64, opponent: u64, reply: i32) -> (u64, u64) {
+    if reply < 0 {
+        (myself, opponent)
+    } else {
+        let mut o: u64 = 0;
+        let mut s: u64 = 0;
+        place(opponent, myself, reply, &mut o, &mut s);
+        (s, o)
+    }
+}
+
+/// 相手の手番の局面で先読みを始め、先読みのハンドルを返す
+///
+/// myself は自分の石、opponent は次に打つ相手の石を表す
+///
+/// predicted は予想する相手の手のビット番号で、負の値の場合は浅い探索によって予想する
+///
+/// 返されたハンドルは ponder_resolve か ponder_cancel によって必ず解放する必要がある
+#[unsafe(no_mangle)]
+pub extern "C" fn ponder_start(
+    myself: u64,
+    opponent: u64,
+    predicted: i32,
+    concurrency: i32,
+) -> *mut Ponder {
+    Box::into_raw(Box::new(Ponder::start(
+        myself,
+        opponent,
+        predicted,
+        concurrency,
+    )))
+}
+
+/// 設定 config に従って ponder_start と同じ先読みを始め、先読みのハンドルを返す
+///
+/// 設定は呼び出し時に複製されるので、config はこの関数から戻った後に変更や解放をしてもよい
+///
+/// # Safety
+///
+/// config は engine_config_new が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ponder_start_with_config(
+    config: *const EngineConfig,
+    myself: u64,
+    opponent: u64,
+    predicted: i32,
+    concurrency: i32,
+) -> *mut Ponder {
+    let config = unsafe { *config };
+    Box::into_raw(Box::new(Ponder::start_with_config(
+        config,
+        myself,
+        opponent,
+        predicted,
+        concurrency,
+    )))
+}
+
+/// 先読みで予想した相手の手のビット番号を返す（パスの場合は -1）
+///
+/// # Safety
+///
+/// ponder は ponder_start が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ponder_predicted_move(ponder: *const Ponder) -> i32 {
+    unsafe { (*ponder).predicted_move() }
+}
+
+/// 実際の相手の手 actual（パスの場合は -1）を受け取り、その後の局面での自分の手のビット番号を返す
+///
+/// 予想が当たった場合は先読みの結果を使い、外れた場合は改めて探索する
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// actual が相手の打てる手でない場合や、相手が打てるのに -1 の場合は -1 を返す
+///
+/// 呼び出し後はハンドルが解放される
+///
+/// # Safety
+///
+/// ponder は ponder_start が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ponder_resolve(ponder: *mut Ponder, actual: i32) -> i32 {
+    let ponder = unsafe { Box::from_raw(ponder) };
+    ponder.resolve(actual)
+}
+
+/// 先読みを中断してハンドルを解放する
+///
+/// # Safety
+///
+/// ponder は ponder_start が返した解放されていないハンドルである必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ponder_cancel(ponder: *mut Ponder) {
+    drop(unsafe { Box::from_raw(ponder) });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::choose_move_parallel_with;
+    use crate::game::{Game, parse_position};
+
+    #[test]
+    fn ponder_hit_test() {
+        let (myself, opponent) = parse_position(
+            "
+            --X--O--
+            ---XOO--
+            --OOXOO-
+            -OOOOOO-
+            ---OOO--
+            ----O---
+            --------
+            -------- X",
+        );
+        let ponder = Ponder::start(opponent, myself, -1, 2);
+        let predicted = ponder.predicted_move();
+        assert_ne!(0, possible_moves(myself, opponent) & (1 << predicted));
+        let (s, o) = after_reply(opponent, myself, predicted);
+        assert_eq!(
+            choose_move_parallel_with(s, o, 2),
+            ponder.resolve(predicted)
+        );
+    }
+
+    #[test]
+    fn ponder_miss_test() {
+        // 相手の O の手番の終盤の局面
+        let (opponent, myself) = parse_position(
+            "
+            XXOOXXXO
+            OOOOOXXO
+            OOXOOOXO
+            OOXXOXOO
+            OOOOXOOO
+            OOOXOOOO
+            O-OOOOOO
+            --OOOOXO O",
+        );
+        let moves = possible_moves(opponent, myself);
+        let predicted = moves.trailing_zeros() as i32;
+        let actual = 63 - moves.leading_zeros() as i32;
+        assert_ne!(predicted, actual);
+        let ponder = Ponder::start(myself, opponent, predicted, 2);
+        let (s, o) = after_reply(myself, opponent, actual);
+        assert_eq!(choose_move_parallel_with(s, o, 2), ponder.resolve(actual));
+    }
+
+    #[test]
+    fn ponder_illegal_reply_test() {
+        // 初期局面で相手の X が打つ前に先読みを始める
+        let game = Game::new();
+        let (opponent, myself) = (game.myself(), game.opponent());
+        let start = || Ponder::start(myself, opponent, -1, 2);
+        // 相手の打てない手、盤外のビット番号、相手が打てるのにパスの場合は探索しない
+        assert_eq!(-1, start().resolve(0));
+        assert_eq!(-1, start().resolve(64));
+        assert_eq!(-1, start().resolve(-1));
+        assert_eq!(-1, start().resolve(-2));
+        assert_eq!(-1, unsafe {
+            ponder_resolve(ponder_start(myself, opponent, -1, 2), 0)
+        });
+        // 相手の打てる手なら探索する
+        let actual = possible_moves(opponent, myself).trailing_zeros() as i32;
+        assert_ne!(-1, start().resolve(actual));
+    }
+
+    #[test]
+    fn ponder_cancel_test() {
+        // 探索の途中で破棄しても終了を待てる
+        // 初期局面で相手の X が打つ前に先読みを始める
+        let game = Game::new();
+        let (opponent, myself) = (game.myself(), game.opponent());
+        let ponder = ponder_start(myself, opponent, -1, 2);
+        unsafe { ponder_cancel(ponder) };
+    }
+}
@@ -0,0 +1,331 @@
+use crate::random::Random;
+use crate::{
+    EngineConfig, INTMAX, INTMIN, NEVER_STOP, SearchContext, count_bits, heuristic_search_sub,
+    place, possible_moves,
+};
+
+/// ProbCut で深い探索の値を浅い探索の値から予測する回帰のパラメータ
+///
+/// 深さ depth の探索の値を、深さ shallow_depth の探索の値 v から slope * v + intercept と予測し、予測の誤差の標準偏差を sigma とする
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProbCutParameter {
+    /// 予測する探索の深さ
+    pub depth: i32,
+    /// 予測に用いる浅い探索の深さ
+    pub shallow_depth: i32,
+    /// 回帰直線の傾き
+    pub slope: f64,
+    /// 回帰直線の切片
+    pub intercept: f64,
+    /// 予測の誤差の標準偏差
+    pub sigma: f64,
+}
+
+// 既定の評価関数で fit_probcut によって求めたパラメータ（`reversi probcut --samples 250 --depth 9` で生成した）
+//
+// これより深い探索では、最も深いパラメータを同じ深さの差で用いる
+const PARAMETERS: [ProbCutParameter; 7] = [
+    ProbCutParameter {
+        depth: 3,
+        shallow_depth: 1,
+        slope: 1.0022,
+        intercept: -677.6,
+        sigma: 2606.4,
+    },
+    ProbCutParameter {
+        depth: 4,
+        shallow_depth: 2,
+        slope: 1.0143,
+        intercept: -12.9,
+        sigma: 2264.9,
+    },
+    ProbCutParameter {
+        depth: 5,
+        shallow_depth: 1,
+        slope: 1.0227,
+        intercept: -1012.5,
+        sigma: 3474.0,
+    },
+    ProbCutParameter {
+        depth: 6,
+        shallow_depth: 2,
+        slope: 1.0332,
+        intercept: 359.3,
+        sigma: 3152.6,
+    },
+    ProbCutParameter {
+        depth: 7,
+        shallow_depth: 3,
+        slope: 1.0347,
+        intercept: -392.8,
+        sigma: 3170.4,
+    },
+    ProbCutParameter {
+        depth: 8,
+        shallow_depth: 4,
+        slope: 1.0602,
+        intercept: 689.7,
+        sigma: 2895.6,
+    },
+    ProbCutParameter {
+        depth: 9,
+        shallow_depth: 3,
+        slope: 1.0684,
+        intercept: -583.1,
+        sigma: 4105.9,
+    },
+];
+
+// 深さ depth の探索で用いるパラメータを返す（ProbCut をしない深さでは None を返す）
+fn parameter(depth: i32) -> Option<ProbCutParameter> {
+    let first = PARAMETERS.first()?;
+    let last = PARAMETERS.last()?;
+    if depth < first.depth {
+        None
+    } else if depth <= last.depth {
+        Some(PARAMETERS[(depth - first.depth) as usize])
+    } else {
+        Some(ProbCutParameter {
+            depth,
+            shallow_depth: depth - (last.depth - last.shallow_depth),
+            ..*last
+        })
+    }
+}
+
+// 根からの手数が ply の局面の深さ depth の探索で浅い探索の値を調べ、値が窓 (alpha, beta) の外にあると予測できる場合は打ち切った値を返す
+//
+// 設定の probcut_threshold が 0 以下の場合は何もしない
+pub(crate) fn probcut(
+    ctx: &mut SearchContext,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    ply: usize,
+    alpha: i32,
+    beta: i32,
+) -> Option<i32> {
+    if ctx.config.probcut_threshold <= 0 {
+        return None;
+    }
+    let p = parameter(depth)?;
+    let margin = ctx.config.probcut_threshold as f64 / 100.0 * p.sigma;
+    // 勝敗が決まった局面の評価値を含む窓では予測しない
+    let limit = ctx.config.confident_victory / 2;
+    if beta < limit {
+        // 浅い探索の値が bound 以上なら、深い探索の値は高い確率で beta 以上になる
+        let bound = ((beta as f64 + margin - p.intercept) / p.slope).ceil() as i32;
+        if bound < limit
+            && heuristic_search_sub(
+                ctx,
+                myself,
+                opponent,
+                p.shallow_depth,
+                ply,
+                bound - 1,
+                bound,
+            ) >= bound
+        {
+            return Some(beta);
+        }
+    }
+    if alpha > -limit {
+        // 浅い探索の値が bound 以下なら、深い探索の値は高い確率で alpha 以下になる
+        let bound = ((alpha as f64 - margin - p.intercept) / p.slope).floor() as i32;
+        if bound > -limit
+            && heuristic_search_sub(
+                ctx,
+                myself,
+                opponent,
+                p.shallow_depth,
+                ply,
+                bound,
+                bound + 1,
+            ) <= bound
+        {
+            return Some(alpha);
+        }
+    }
+    None
+}
+
+// 深さ depth の予測に用いる浅い探索の深さ（深さの半分程度で、深さと偶奇が等しい）
+fn shallow_depth(depth: i32) -> i32 {
+    let s = depth / 2;
+    if (depth - s) % 2 == 0 { s } else { s - 1 }
+}
+
+// 初期局面から盤上の石が discs 個になるまで乱数で打った局面を返す（途中で終局した場合は None）
+pub(crate) fn sample_position(random: &mut Random, discs: i32) -> Option<(u64, u64)> {
+    let mut myself: u64 = 0x0000_0008_1000_0000;
+    let mut opponent: u64 = 0x0000_0010_0800_0000;
+    while count_bits(myself | opponent) < discs {
+        let moves = possible_moves(myself, opponent);
+        if moves == 0 {
+            if possible_moves(opponent, myself) == 0 {
+                return None;
+            }
+        } else {
+            let mut m = moves;
+            for _ in 0..random.below(count_bits(moves) as u64) {
+                m &= m - 1;
+            }
+            let mut s: u64 = 0;
+            let mut o: u64 = 0;
+            place(myself, opponent, m.trailing_zeros() as i32, &mut s, &mut o);
+            (myself, opponent) = (s, o);
+        }
+        (myself, opponent) = (opponent, myself);
+    }
+    Some((myself, opponent))
+}
+
+/// 乱数で作った samples 個の中盤の局面を既定の評価関数で探索し、深さ 3 から max_depth までの ProbCut のパラメータを求める
+///
+/// 各深さの探索の値を、深さの半分程度の浅い探索の値から最小二乗法で予測する回帰直線を求める
+///
+/// 勝敗が決まった局面の評価値は回帰に含めない
+pub fn fit_probcut(samples: usize, max_depth: i32, seed: u64) -> Vec<ProbCutParameter> {
+    let config = EngineConfig::DEFAULT;
+    let limit = config.confident_victory / 2;
+    let mut random = Random::new(seed);
+    // 局面ごとの深さ 0 から max_depth までの探索の値
+    let mut values: Vec<Vec<i32>> = Vec::new();
+    while values.len() < samples {
+        let discs = 12 + random.below(33) as i32;
+        let Some((myself, opponent)) = sample_position(&mut random, discs) else {
+            continue;
+        };
+        if possible_moves(myself, opponent) == 0 {
+            continue;
+        }
+        let mut ctx = SearchContext::new(&config, &NEVER_STOP);
+        values.push(
+            (0..=max_depth)
+                .map(|depth| {
+                    heuristic_search_sub(&mut ctx, myself, opponent, depth, 0, INTMIN, INTMAX)
+                })
+                .collect(),
+        );
+    }
+    (3..=max_depth)
+        .map(|depth| {
+            let shallow = shallow_depth(depth);
+            let pairs: Vec<(f64, f64)> = values
+                .iter()
+                .map(|v| (v[shallow as usize], v[depth as usize]))
+                .filter(|&(x, y)| x.abs() < limit && y.abs() < limit)
+                .map(|(x, y)| (x as f64, y as f64))
+                .collect();
+            let n = pairs.len() as f64;
+            let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+            let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+            let sxy: f64 = pairs
+                .iter()
+                .map(|&(x, y)| (x - mean_x) * (y - mean_y))
+                .sum();
+            let sxx: f64 = pairs
+                .iter()
+                .map(|&(x, _)| (x - mean_x) * (x - mean_x))
+                .sum();
+            let slope = sxy / sxx;
+            let intercept = mean_y - slope * mean_x;
+            let sigma = (pairs
+                .iter()
+                .map(|&(x, y)| (y - slope * x - intercept).powi(2))
+                .sum::<f64>()
+                / n)
+                .sqrt();
+            ProbCutParameter {
+                depth,
+                shallow_depth: shallow,
+                slope,
+                intercept,
+                sigma,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_position;
+
+    // 序盤の局面
+    const MIDGAME: &str = "
+        --X--O--
+        ---XOO--
+        --OOXOO-
+        -OOOOOO-
+        ---OOO--
+        ----O---
+        --------
+        -------- X";
+
+    #[test]
+    fn parameters_test() {
+        for (k, p) in PARAMETERS.iter().enumerate() {
+            assert_eq!(3 + k as i32, p.depth);
+            assert_eq!(shallow_depth(p.depth), p.shallow_depth);
+            assert!(p.slope > 0.0 && p.sigma > 0.0);
+        }
+        assert_eq!(None, parameter(2));
+        // 最も深いパラメータより深い探索では、同じ深さの差で浅い探索をする
+        let last = PARAMETERS.last().unwrap();
+        let p = parameter(last.depth + 4).unwrap();
+        assert_eq!(last.shallow_depth + 4, p.shallow_depth);
+        assert_eq!(last.sigma, p.sigma);
+    }
+
+    #[test]
+    fn probcut_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let search = |config: &EngineConfig| {
+            let mut ctx = SearchContext::new(config, &NEVER_STOP);
+            let v = heuristic_search_sub(&mut ctx, myself, opponent, 8, 0, INTMIN, INTMAX);
+            (v, ctx.nodes)
+        };
+        let (value, nodes) = search(&EngineConfig::DEFAULT);
+        let config = EngineConfig {
+            probcut_threshold: 150,
+            ..EngineConfig::DEFAULT
+        };
+        let (probcut_value, probcut_nodes) = search(&config);
+        // 枝刈りによって節点の数が減り、値は大きくは変わらない
+        assert!(probcut_nodes * 2 < nodes, "{probcut_nodes} / {nodes}");
+        let sigma = parameter(8).unwrap().sigma;
+        assert!(((probcut_value - value) as f64).abs() < 3.0 * sigma);
+        assert!(
+            possible_moves(myself, opponent)
+                & crate::index_to_bit(config.heuristic_search(myself, opponent, 9))
+                != 0
+        );
+    }
+
+    #[test]
+    fn shallow_depth_test() {
+        for depth in 3..=20 {
+            let shallow = shallow_depth(depth);
+            assert!(0 < shallow && shallow <= depth / 2);
+            assert_eq!(depth % 2, shallow % 2);
+        }
+    }
+
+    #[test]
+    fn fit_test() {
+        let parameters = fit_probcut(20, 4, 47);
+        assert_eq!(
+            vec![3, 4],
+            parameters.iter().map(|p| p.depth).collect::<Vec<_>>()
+        );
+        for p in parameters {
+            assert_eq!(shallow_depth(p.depth), p.shallow_depth);
+            // 浅い探索と深い探索の値には正の相関がある
+            assert!(p.slope > 0.0);
+            assert!(p.sigma > 0.0);
+        }
+        // 同じシード値からは同じパラメータが求まる
+        assert_eq!(fit_probcut(5, 3, 1), fit_probcut(5, 3, 1));
+    }
+}
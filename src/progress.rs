@@ -0,0 +1,334 @@
+use crate::{
+    EngineConfig, INTMAX, INTMIN, NEVER_STOP, SearchContext, SearchShared, count_bits,
+    full_search_sub, is_better_move, place, possible_moves, search_root_move,
+};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 探索の途中経過
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchProgress {
+    /// 探索している深さ（完全探索では空きマスの数）
+    pub depth: i32,
+    /// これまでに見つかった最良の手のビット番号（まだない場合は -1）
+    pub best_move: i32,
+    /// 最良の手の評価値（完全探索では終局時の石数の差）
+    pub score: i32,
+    /// これまでに探索した節点の数
+    pub nodes: u64,
+    /// 完全探索かどうか
+    pub exact: bool,
+}
+
+/// 探索の途中経過を受け取る C の関数
+///
+/// 探索している深さ、これまでに見つかった最良の手のビット番号、その評価値、探索した節点の数、呼び出し側が渡したポインタを受け取る
+pub type ProgressCallback = Option<
+    unsafe extern "C" fn(
+        depth: i32,
+        best_move: i32,
+        score: i32,
+        nodes: u64,
+        user_data: *mut c_void,
+    ),
+>;
+
+// 設定 config に従い、interval ごとに途中経過を report に渡しながら choose_move_parallel_with と同じ探索をする
+//
+// 部分探索では深さ 1 から順に反復して深くし、深さごとの探索を終えたときにも report を呼ぶ
+//
+// 打つ手がない場合は best_move が -1 の結果を返し、report は呼ばない
+//
+// 中断が要求された場合は意味のない結果を返す
+pub(crate) fn search_with_progress(
+    config: &EngineConfig,
+    myself: u64,
+    opponent: u64,
+    concurrency: i32,
+    interval: Duration,
+    shared: &SearchShared,
+    report: &mut dyn FnMut(&SearchProgress),
+) -> SearchProgress {
+    let mut progress = SearchProgress {
+        depth: 0,
+        best_move: -1,
+        score: 0,
+        nodes: 0,
+        exact: false,
+    };
+    // 打てる手と、打った後の局面と開放度の評価値を並べる
+    let mut children = Vec::new();
+    let mut m = possible_moves(myself, opponent);
+    while m != 0 {
+        let i = m.trailing_zeros() as i32;
+        m &= m - 1;
+        let mut s: u64 = 0;
+        let mut o: u64 = 0;
+        let turns = place(myself, opponent, i, &mut s, &mut o);
+        let opns = config.openness_evaluation(myself, opponent, turns);
+        children.push((i, s, o, opns));
+    }
+    if children.is_empty() {
+        return progress;
+    }
+    let (depths, exact) = match config.parallel_search_depth(myself, opponent) {
+        Some(depth) => (1..=depth, false),
+        None => {
+            let empties = 64 - count_bits(myself | opponent);
+            (empties..=empties, true)
+        }
+    };
+    progress.exact = exact;
+    let counter = AtomicU64::new(0);
+    let mut next_report = Instant::now() + interval;
+    for depth in depths {
+        progress.depth = depth;
+        let next = AtomicUsize::new(0);
+        // スレッド間で共有する、この深さで探索を終えた手の最良の評価値
+        let best = AtomicI32::new(INTMIN);
+        let mut best_v = INTMIN;
+        let mut best_i = -1;
+        thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+            for _ in 0..concurrency.clamp(1, children.len() as i32) {
+                let sender = sender.clone();
+                let (next, best, counter, children) = (&next, &best, &counter, &children);
+                scope.spawn(move || {
+                    loop {
+                        let k = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(&(i, s, o, opns)) = children.get(k) else {
+                            break;
+                        };
+                        let mut ctx = SearchContext::with_counter(config, shared, counter);
+                        let value = if exact {
+                            -full_search_sub(&mut ctx, o, s, INTMIN, INTMAX)
+                        } else {
+                            let bound = best.load(Ordering::Relaxed);
+                            let value =
+                                search_root_move(&mut ctx, o, s, depth - 1, opns, bound, INTMAX);
+                            best.fetch_max(value, Ordering::Relaxed);
+                            value
+                        };
+                        ctx.flush_counter();
+                        sender.send((i, value)).unwrap();
+                    }
+                });
+            }
+            drop(sender);
+            loop {
+                let timeout = next_report.saturating_duration_since(Instant::now());
+                match receiver.recv_timeout(timeout) {
+                    Ok((i, v)) => {
+                        if is_better_move(v, i, best_v, best_i) {
+                            best_v = v;
+                            best_i = i;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                if Instant::now() >= next_report {
+                    // この深さで探索を終えた手がまだなければ、1つ浅い探索の結果を報告する
+                    if best_i >= 0 {
+                        progress.best_move = best_i;
+                        progress.score = best_v;
+                    }
+                    progress.nodes = counter.load(Ordering::Relaxed);
+                    report(&progress);
+                    next_report = Instant::now() + interval;
+                }
+            }
+        });
+        progress.best_move = best_i;
+        progress.score = best_v;
+        progress.nodes = counter.load(Ordering::Relaxed);
+        if shared.stop.check() {
+            break;
+        }
+        report(&progress);
+    }
+    progress
+}
+
+impl EngineConfig {
+    /// この設定で choose_move_parallel_with と同じ手を探索し、最後の途中経過を返す
+    ///
+    /// 探索の途中経過を interval ごとに report に渡す（report は呼び出したスレッドで呼ばれる）
+    ///
+    /// 部分探索では深さ 1 から順に反復して深くし、深さごとの探索を終えたときにも report を呼ぶ
+    ///
+    /// 途中経過の最良の手は、探索中の深さで探索を終えた手の中で最良の手（そのような手がまだない場合は1つ浅い探索の最良の手）
+    ///
+    /// 打つ手がない場合は best_move が -1 の結果を返し、report は呼ばない
+    pub fn choose_move_with_progress(
+        &self,
+        myself: u64,
+        opponent: u64,
+        concurrency: i32,
+        interval: Duration,
+        mut report: impl FnMut(&SearchProgress),
+    ) -> SearchProgress {
+        search_with_progress(
+            self,
+            myself,
+            opponent,
+            concurrency,
+            interval,
+            &NEVER_STOP,
+            &mut report,
+        )
+    }
+}
+
+// C の関数 callback を呼び出す report を作る
+pub(crate) fn c_report(
+    callback: ProgressCallback,
+    user_data: *mut c_void,
+) -> impl FnMut(&SearchProgress) {
+    move |progress| {
+        if let Some(callback) = callback {
+            unsafe {
+                callback(
+                    progress.depth,
+                    progress.best_move,
+                    progress.score,
+                    progress.nodes,
+                    user_data,
+                )
+            };
+        }
+    }
+}
+
+/// choose_move_parallel_with と同じ探索をし、探索の途中経過を callback に渡しながら最良の手のビット番号を返す
+///
+/// 打つ手がない場合は -1 を返す
+///
+/// callback は呼び出したスレッドで interval_ms ミリ秒ごとと、反復して深くする部分探索で深さごとの探索を終えたときに呼ばれる
+///
+/// callback には user_data がそのまま渡される
+///
+/// # Safety
+///
+/// callback は NULL か、user_data を受け取って呼び出せる関数である必要がある
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn choose_move_with_progress(
+    myself: u64,
+    opponent: u64,
+    concurrency: i32,
+    interval_ms: u32,
+    callback: ProgressCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    EngineConfig::DEFAULT
+        .choose_move_with_progress(
+            myself,
+            opponent,
+            concurrency,
+            Duration::from_millis(interval_ms as u64),
+            c_report(callback, user_data),
+        )
+        .best_move
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_position;
+    use crate::{choose_move_parallel_with, full_search_stats};
+
+    // 序盤の局面
+    const MIDGAME: &str = "
+        --X--O--
+        ---XOO--
+        --OOXOO-
+        -OOOOOO-
+        ---OOO--
+        ----O---
+        --------
+        -------- X";
+
+    // 空きマスが 10 の終盤の局面
+    const ENDGAME: &str = "
+        XOOOOX--
+        XXXOOO--
+        XOOXOXOO
+        XOXXOXXO
+        XOOOOOXO
+        XXOOOXXO
+        --OOXO-O
+        --OOOO-O X";
+
+    #[test]
+    fn heuristic_progress_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let config = EngineConfig::DEFAULT;
+        let mut reports = Vec::new();
+        let result =
+            config.choose_move_with_progress(myself, opponent, 2, Duration::from_secs(60), |p| {
+                reports.push(*p)
+            });
+        assert_eq!(
+            choose_move_parallel_with(myself, opponent, 2),
+            result.best_move
+        );
+        // 深さごとに報告され、最後の報告は結果と等しい
+        let depth = config.parallel_search_depth(myself, opponent).unwrap();
+        assert_eq!(
+            (1..=depth).collect::<Vec<_>>(),
+            reports.iter().map(|p| p.depth).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&result), reports.last());
+        assert!(!result.exact);
+        assert!(reports.windows(2).all(|w| w[0].nodes < w[1].nodes));
+    }
+
+    #[test]
+    fn full_progress_test() {
+        let (myself, opponent) = parse_position(ENDGAME);
+        let stats = full_search_stats(myself, opponent);
+        let mut reports = Vec::new();
+        let result = EngineConfig::DEFAULT.choose_move_with_progress(
+            myself,
+            opponent,
+            1,
+            Duration::from_millis(1),
+            |p| reports.push(*p),
+        );
+        assert_eq!(stats.best_move, result.best_move);
+        assert_eq!(stats.score, result.score);
+        assert!(result.exact);
+        assert_eq!(10, result.depth);
+        assert_eq!(Some(&result), reports.last());
+        assert!(reports.windows(2).all(|w| w[0].nodes <= w[1].nodes));
+    }
+
+    extern "C" fn record(depth: i32, best_move: i32, _: i32, _: u64, user_data: *mut c_void) {
+        let reports = unsafe { &mut *(user_data as *mut Vec<(i32, i32)>) };
+        reports.push((depth, best_move));
+    }
+
+    #[test]
+    fn progress_callback_test() {
+        let (myself, opponent) = parse_position(MIDGAME);
+        let mut reports: Vec<(i32, i32)> = Vec::new();
+        let best = unsafe {
+            choose_move_with_progress(
+                myself,
+                opponent,
+                1,
+                60000,
+                Some(record),
+                &mut reports as *mut Vec<(i32, i32)> as *mut c_void,
+            )
+        };
+        assert_eq!(choose_move_parallel_with(myself, opponent, 1), best);
+        assert_eq!(Some(&best), reports.last().map(|(_, i)| i));
+        assert_eq!(best, unsafe {
+            choose_move_with_progress(myself, opponent, 1, 0, None, std::ptr::null_mut())
+        });
+    }
+}
@@ -0,0 +1,55 @@
+// シード値から再現可能な乱数列を生成する擬似乱数生成器
+//
+// アルゴリズムには SplitMix64 を用いる
+pub(crate) struct Random {
+    state: u64,
+}
+
+impl Random {
+    // シード値から乱数生成器を作る
+    pub(crate) fn new(seed: u64) -> Self {
+        Random { state: seed }
+    }
+
+    // 64 ビットの乱数を返す
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // 0 以上 n 未満の一様な乱数を返す
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducibility_test() {
+        let mut a = Random::new(12345);
+        let mut b = Random::new(12345);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let mut c = Random::new(54321);
+        assert_ne!(a.next_u64(), c.next_u64());
+    }
+
+    #[test]
+    fn range_test() {
+        let mut r = Random::new(0);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let x = r.below(6);
+            assert!(x < 6);
+            seen[x as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+}
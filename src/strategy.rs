@@ -0,0 +1,351 @@
+use crate::{
+    EngineConfig, INTMAX, INTMIN, SearchContext, SearchShared, WindowResult, place, possible_moves,
+    search_root_move,
+};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// 根の手をそれぞれ全幅の窓で探索する根の探索の戦略（既定）
+pub const REVERSI_STRATEGY_FULL_WINDOW: i32 = 0;
+/// 反復深化で1つ浅い探索の評価値を中心とする狭い窓で探索し、窓を外れた場合は窓を広げて探索し直す根の探索の戦略
+pub const REVERSI_STRATEGY_ASPIRATION: i32 = 1;
+/// 反復深化で1つ浅い探索の評価値から始めて、幅のない窓の探索を繰り返して評価値を絞り込む根の探索の戦略（MTD(f)）
+///
+/// 部分探索は置換表を持たないので、全幅の窓で探索するよりも遅い
+pub const REVERSI_STRATEGY_MTDF: i32 = 2;
+
+// 根の手（ビット番号、手を打った後の相手の石と自分の石、開放度の評価値）
+type RootMove = (i32, u64, u64, i32);
+
+// 根の手をビット番号の順に返す
+fn root_moves(config: &EngineConfig, myself: u64, opponent: u64) -> Vec<RootMove> {
+    let mut children = Vec::new();
+    let mut m = possible_moves(myself, opponent);
+    while m != 0 {
+        let i = m.trailing_zeros() as i32;
+        let mut s: u64 = 0;
+        let mut o: u64 = 0;
+        let turns = place(myself, opponent, i, &mut s, &mut o);
+        children.push((i, o, s, config.openness_evaluation(myself, opponent, turns)));
+        m &= m - 1;
+    }
+    children
+}
+
+// 根の手の評価値を集めて、根の評価値が lower 以上 upper 未満かを調べる
+struct RootWindow {
+    lower: i32,
+    upper: i32,
+    // 評価値が lower 以上の手のうち最良の評価値とその手
+    best: i32,
+    chosen: i32,
+    // 評価値が lower 未満の手の評価値の上界の最大値
+    bound: i32,
+    // 評価値が upper 以上の手のうち最大の評価値の下界とその手
+    high: Option<(i32, i32)>,
+}
+
+impl RootWindow {
+    fn new(lower: i32, upper: i32) -> Self {
+        RootWindow {
+            lower,
+            upper,
+            best: lower,
+            chosen: -1,
+            bound: INTMIN,
+            high: None,
+        }
+    }
+
+    // ビット番号の順に手 i の評価値 v を加える
+    fn add(&mut self, i: i32, v: i32) {
+        if v >= self.upper {
+            if self.high.is_none_or(|(high, _)| v > high) {
+                self.high = Some((v, i));
+            }
+        } else if v >= self.lower {
+            if self.chosen < 0 || v > self.best {
+                self.best = v;
+                self.chosen = i;
+            }
+        } else {
+            self.bound = self.bound.max(v);
+        }
+    }
+
+    // 根の評価値の結果と、その手のビット番号を返す（評価値が lower 未満の場合は手を -1 とする）
+    fn result(&self) -> (WindowResult, i32) {
+        match self.high {
+            Some((v, i)) => (WindowResult::FailHigh(v), i),
+            None if self.chosen >= 0 => (WindowResult::Exact(self.best), self.chosen),
+            None => (WindowResult::FailLow(self.bound), -1),
+        }
+    }
+}
+
+// 根の手 children を深さ depth で探索し、根の評価値が lower 以上 upper 未満かを調べる
+//
+// Exact の場合は、全幅の窓で探索した場合と同じ手を返す
+fn search_window(
+    ctx: &mut SearchContext,
+    children: &[RootMove],
+    depth: i32,
+    lower: i32,
+    upper: i32,
+) -> (WindowResult, i32) {
+    let mut window = RootWindow::new(lower, upper);
+    for &(i, o, s, opns) in children {
+        let v = search_root_move(ctx, o, s, depth, opns, window.best, upper);
+        window.add(i, v);
+        if window.high.is_some() {
+            break;
+        }
+    }
+    window.result()
+}
+
+// search_window を根の手ごとにスレッドを分けて並列に行う
+fn search_window_parallel(
+    config: &EngineConfig,
+    children: &[RootMove],
+    depth: i32,
+    lower: i32,
+    upper: i32,
+    concurrency: i32,
+    shared: &SearchShared,
+) -> (WindowResult, i32) {
+    // スレッド間で共有する、探索を終えた手の最良の評価値
+    let best = AtomicI32::new(lower);
+    let mut window = RootWindow::new(lower, upper);
+    thread::scope(|scope| {
+        let mut handles = Vec::new();
+        let (sender, receiver) = mpsc::channel();
+        for (k, &(i, o, s, opns)) in children.iter().enumerate() {
+            // 並列性を制限
+            if k as i32 >= concurrency {
+                receiver.recv().unwrap();
+            }
+            let sender = sender.clone();
+            let best = &best;
+            let handle = scope.spawn(move || {
+                // 次のスレッドが表を使えるように、終了を知らせる前に探索の状態を捨てる
+                let value = {
+                    let mut ctx = SearchContext::new(config, shared);
+                    // 他の手が upper 以上になっていても、窓は空にしない
+                    let threshold = best.load(Ordering::Relaxed).min(upper - 1);
+                    search_root_move(&mut ctx, o, s, depth, opns, threshold, upper)
+                };
+                best.fetch_max(value, Ordering::Relaxed);
+                sender.send(()).unwrap();
+                value
+            });
+            handles.push((i, handle));
+        }
+        for (i, handle) in handles {
+            window.add(i, handle.join().unwrap());
+        }
+    });
+    window.result()
+}
+
+// 前の深さの評価値と手 previous から、戦略 config.root_strategy に従って窓を指定する根の探索 search を繰り返し、評価値と最良の手を求める
+//
+// 中断された場合は、それまでに分かった手か previous の手を返す
+fn drive(
+    config: &EngineConfig,
+    previous: (i32, i32),
+    shared: &SearchShared,
+    mut search: impl FnMut(i32, i32) -> (WindowResult, i32),
+) -> (i32, i32) {
+    let (guess, previous_move) = previous;
+    // 窓を指定して探索し、正確な評価値が求まった場合や中断された場合は Err にする
+    let mut step = |lower: i32, upper: i32| {
+        let (result, i) = search(lower, upper);
+        match result {
+            WindowResult::Exact(v) => Err((v, i)),
+            _ if shared.stop.check() => Err((guess, if i >= 0 { i } else { previous_move })),
+            WindowResult::FailHigh(v) => Ok((true, v)),
+            WindowResult::FailLow(v) => Ok((false, v)),
+        }
+    };
+    if config.root_strategy == REVERSI_STRATEGY_MTDF {
+        // 根の評価値は lower 以上 upper 以下
+        let (mut lower, mut upper) = (INTMIN, INTMAX);
+        let mut test = guess;
+        // 限界が片側しか分からない間に、限界から離して調べる幅
+        let mut delta = config.aspiration_window.max(1);
+        loop {
+            match step(test, test.saturating_add(1)) {
+                Err(found) => return found,
+                Ok((true, v)) => lower = v,
+                Ok((false, v)) => upper = v,
+            }
+            // 評価関数の値は細かいので、求まった限界そのものを調べると収束が遅い
+            test = if lower == INTMIN {
+                upper.saturating_sub(delta)
+            } else if upper == INTMAX {
+                lower.saturating_add(delta)
+            } else {
+                lower.midpoint(upper)
+            }
+            .max(lower)
+            .min(upper);
+            delta = delta.saturating_mul(2);
+        }
+    }
+    // 窓を外れるたびに幅を倍にして、外れた側に窓を移す
+    let mut delta = config.aspiration_window.max(1);
+    let mut lower = guess.saturating_sub(delta).max(INTMIN);
+    let mut upper = guess.saturating_add(delta);
+    loop {
+        let (high, v) = match step(lower, upper) {
+            Err(found) => return found,
+            Ok(failed) => failed,
+        };
+        delta = delta.saturating_mul(2);
+        if high {
+            lower = v;
+            upper = v.saturating_add(delta);
+        } else {
+            lower = v.saturating_sub(delta).max(INTMIN);
+            upper = v.saturating_add(1);
+        }
+    }
+}
+
+// 反復深化で深さ 1 から depth まで、窓を指定する根の探索 search を戦略 config.root_strategy に従って繰り返し、最良の手を返す
+//
+// search は根の深さ、窓の下限と上限を受け取る
+fn iterative_search(
+    config: &EngineConfig,
+    depth: i32,
+    shared: &SearchShared,
+    mut search: impl FnMut(i32, i32, i32) -> (WindowResult, i32),
+) -> i32 {
+    // 最初の深さは全幅の窓で探索する
+    let first = depth.min(1);
+    let mut previous = match search(first, INTMIN, INTMAX) {
+        (WindowResult::Exact(v), i) => (v, i),
+        (_, i) => (0, i),
+    };
+    for d in first + 1..=depth {
+        previous = drive(config, previous, shared, |lower, upper| {
+            search(d, lower, upper)
+        });
+        if shared.stop.check() {
+            break;
+        }
+    }
+    previous.1
+}
+
+// 逐次版の heuristic_search_root を戦略 config.root_strategy に従って行う
+pub(crate) fn heuristic_search_iterative(
+    config: &EngineConfig,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    shared: &SearchShared,
+) -> i32 {
+    let children = root_moves(config, myself, opponent);
+    if children.is_empty() {
+        return -1;
+    }
+    let mut ctx = SearchContext::new(config, shared);
+    iterative_search(config, depth, shared, |d, lower, upper| {
+        search_window(&mut ctx, &children, d - 1, lower, upper)
+    })
+}
+
+// 並列版の heuristic_search_parallel_sub を戦略 config.root_strategy に従って行う
+pub(crate) fn heuristic_search_parallel_iterative(
+    config: &EngineConfig,
+    myself: u64,
+    opponent: u64,
+    depth: i32,
+    concurrency: i32,
+    shared: &SearchShared,
+) -> i32 {
+    let children = root_moves(config, myself, opponent);
+    if children.is_empty() {
+        return -1;
+    }
+    iterative_search(config, depth, shared, |d, lower, upper| {
+        search_window_parallel(config, &children, d - 1, lower, upper, concurrency, shared)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_position;
+    use crate::{NEVER_STOP, heuristic_search_root};
+
+    // 序盤の局面
+    const MIDGAME: &str = "
+        --X--O--
+        ---XOO--
+        --OOXOO-
+        -OOOOOO-
+        ---OOO--
+        ----O---
+        --------
+        -------- X";
+
+    #[test]
+    fn window_test() {
+        let config = &EngineConfig::DEFAULT;
+        let (myself, opponent) = parse_position(MIDGAME);
+        let children = root_moves(config, myself, opponent);
+        let mut ctx = SearchContext::new(config, &NEVER_STOP);
+        let (full, chosen) = search_window(&mut ctx, &children, 4, INTMIN, INTMAX);
+        let WindowResult::Exact(score) = full else {
+            panic!("{full:?}");
+        };
+        assert_eq!(chosen, heuristic_search_root(config, myself, opponent, 5));
+        // 窓に評価値を含む場合は同じ手が求まり、外れた場合は外れた向きの限界が求まる
+        for (lower, upper) in [(score, score + 1), (score - 100, score + 100)] {
+            assert_eq!(
+                (WindowResult::Exact(score), chosen),
+                search_window(&mut ctx, &children, 4, lower, upper)
+            );
+            assert_eq!(
+                (WindowResult::Exact(score), chosen),
+                search_window_parallel(config, &children, 4, lower, upper, 3, &NEVER_STOP)
+            );
+        }
+        for (lower, upper) in [(score + 1, score + 2), (score + 100, INTMAX)] {
+            let (result, _) = search_window(&mut ctx, &children, 4, lower, upper);
+            assert!(matches!(result, WindowResult::FailLow(v) if v >= score && v < lower));
+        }
+        for (lower, upper) in [(score - 1, score), (INTMIN, score - 100)] {
+            let (result, i) = search_window(&mut ctx, &children, 4, lower, upper);
+            assert!(matches!(result, WindowResult::FailHigh(v) if v <= score && v >= upper));
+            assert!(i >= 0);
+        }
+    }
+
+    #[test]
+    fn strategy_test() {
+        // どの戦略でも全幅の窓で探索した場合と同じ手が選ばれる
+        let (myself, opponent) = parse_position(MIDGAME);
+        for strategy in [REVERSI_STRATEGY_ASPIRATION, REVERSI_STRATEGY_MTDF] {
+            for aspiration_window in [1, 300, 100000] {
+                let config = EngineConfig {
+                    root_strategy: strategy,
+                    aspiration_window,
+                    ..EngineConfig::DEFAULT
+                };
+                for depth in 1..=6 {
+                    let expected = EngineConfig::DEFAULT.heuristic_search(myself, opponent, depth);
+                    assert_eq!(expected, config.heuristic_search(myself, opponent, depth));
+                    assert_eq!(
+                        expected,
+                        config.heuristic_search_parallel(myself, opponent, depth, 3)
+                    );
+                }
+            }
+        }
+    }
+}
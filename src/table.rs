@@ -0,0 +1,90 @@
+// 完全探索で求めた局面の石数の差の範囲を記録する置換表
+//
+// 同じ位置に記録される局面は新しいものに置き換える
+pub(crate) struct TranspositionTable {
+    // 局面のハッシュ値で引く記録（最初に記録するまで確保しない）
+    entries: Vec<Entry>,
+}
+
+// 置換表の1つの記録
+#[derive(Clone, Copy, Default)]
+struct Entry {
+    myself: u64,
+    opponent: u64,
+    // 石数の差の下界
+    lower: i8,
+    // 石数の差の上界
+    upper: i8,
+}
+
+impl TranspositionTable {
+    // 置換表の記録の数の 2 を底とする対数
+    const SIZE_BITS: u32 = 16;
+
+    // 空の置換表を作る
+    pub(crate) fn new() -> Self {
+        TranspositionTable {
+            entries: Vec::new(),
+        }
+    }
+
+    // 局面を記録する位置を返す
+    fn index(myself: u64, opponent: u64) -> usize {
+        let hash = (myself ^ opponent.rotate_left(32)).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ opponent.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        (hash >> (64 - TranspositionTable::SIZE_BITS)) as usize
+    }
+
+    // 局面の石数の差の下界と上界を返す（記録されていない場合は None）
+    pub(crate) fn probe(&self, myself: u64, opponent: u64) -> Option<(i32, i32)> {
+        let entry = self
+            .entries
+            .get(TranspositionTable::index(myself, opponent))?;
+        if entry.myself == myself && entry.opponent == opponent {
+            Some((entry.lower as i32, entry.upper as i32))
+        } else {
+            None
+        }
+    }
+
+    // 窓 (alpha, beta) で完全探索した局面の値 value から、石数の差の範囲を記録する
+    pub(crate) fn store(&mut self, myself: u64, opponent: u64, alpha: i32, beta: i32, value: i32) {
+        let (lower, upper) = if value <= alpha {
+            (-64, value)
+        } else if value >= beta {
+            (value, 64)
+        } else {
+            (value, value)
+        };
+        if self.entries.is_empty() {
+            self.entries = vec![Entry::default(); 1 << TranspositionTable::SIZE_BITS];
+        }
+        self.entries[TranspositionTable::index(myself, opponent)] = Entry {
+            myself,
+            opponent,
+            lower: lower.clamp(-64, 64) as i8,
+            upper: upper.clamp(-64, 64) as i8,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_test() {
+        let mut table = TranspositionTable::new();
+        assert_eq!(None, table.probe(1, 2));
+        table.store(1, 2, -10, 10, 4);
+        assert_eq!(Some((4, 4)), table.probe(1, 2));
+        assert_eq!(None, table.probe(2, 1));
+        table.store(1, 2, -10, 10, -10);
+        assert_eq!(Some((-64, -10)), table.probe(1, 2));
+        table.store(1, 2, -10, 10, 12);
+        assert_eq!(Some((12, 64)), table.probe(1, 2));
+        // 窓の外の値は石数の差の範囲に収める
+        table.store(1, 2, -2147483647, 2147483647, 2147483647);
+        assert_eq!(Some((64, 64)), table.probe(1, 2));
+    }
+}
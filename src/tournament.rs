@@ -0,0 +1,437 @@
+use crate::analyze;
+use crate::game::Game;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// 対局させるエンジン
+///
+/// 手番のプレイヤーの石、相手の石、対局ごとに異なる乱数の種を受け取り、打つ手のビット番号を返す
+pub type Engine<'a> = &'a (dyn Fn(u64, u64, u64) -> i32 + Sync);
+
+/// 2つのエンジンの対戦の設定
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatchConfig {
+    /// 対局数の上限（開始局面ごとに先後を入れ替えて2局ずつ打つので、偶数に切り上げる）
+    pub games: u32,
+    /// 同時に進める対局の数
+    pub concurrency: i32,
+    /// 開始局面を作るために初期局面から打ち進める手数
+    pub opening_plies: i32,
+    /// 開始局面として採用する局面の評価値の絶対値の上限
+    pub opening_margin: i32,
+    /// 逐次確率比検定による打ち切りの設定（None の場合は打ち切らない）
+    pub sprt: Option<Sprt>,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        MatchConfig {
+            games: 100,
+            concurrency: num_cpus::get() as i32,
+            opening_plies: 6,
+            opening_margin: 1000,
+            sprt: None,
+        }
+    }
+}
+
+/// 逐次確率比検定（SPRT）の設定
+///
+/// 1つ目のエンジンの2つ目に対する Elo レーティングの差が elo0 であるという帰無仮説と elo1 であるという対立仮説を検定する
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprt {
+    /// 帰無仮説のレーティングの差
+    pub elo0: f64,
+    /// 対立仮説のレーティングの差
+    pub elo1: f64,
+    /// 第1種の過誤の確率
+    pub alpha: f64,
+    /// 第2種の過誤の確率
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Sprt {
+            elo0: 0.0,
+            elo1: 20.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+/// 逐次確率比検定の結論
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SprtDecision {
+    /// 帰無仮説を採択した（強くなっていない）
+    H0,
+    /// 対立仮説を採択した（強くなった）
+    H1,
+}
+
+/// 対戦の結果（すべて1つ目のエンジンから見た値）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchResult {
+    /// 勝った対局の数
+    pub wins: u32,
+    /// 引き分けた対局の数
+    pub draws: u32,
+    /// 負けた対局の数
+    pub losses: u32,
+    /// 終局時の自分の石の数の合計
+    pub discs: u32,
+    /// 終局時の相手の石の数の合計
+    pub opponent_discs: u32,
+    /// 逐次確率比検定によって打ち切った場合の結論
+    pub sprt: Option<SprtDecision>,
+}
+
+impl MatchResult {
+    /// 対局の数を返す
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// 勝ちを 1、引き分けを 0.5 とした1局あたりの得点を返す
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64
+    }
+
+    /// 得点から推定した Elo レーティングの差と、その 95% 信頼区間の半分の幅を返す
+    ///
+    /// 全勝や全敗などで推定できない場合は None を返す
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        let n = self.games() as f64;
+        let p = self.score();
+        if !(p > 0.0 && p < 1.0) {
+            return None;
+        }
+        let variance = (self.wins as f64 * (1.0 - p).powi(2)
+            + self.draws as f64 * (0.5 - p).powi(2)
+            + self.losses as f64 * p.powi(2))
+            / n;
+        let error = 1.96 * (variance / n).sqrt();
+        let low = elo_from_score((p - error).max(f64::MIN_POSITIVE));
+        let high = elo_from_score((p + error).min(1.0 - f64::EPSILON));
+        Some((elo_from_score(p), (high - low) / 2.0))
+    }
+
+    /// 逐次確率比検定の対数尤度比を返す
+    pub fn llr(&self, sprt: &Sprt) -> f64 {
+        let n = self.games() as f64;
+        if n == 0.0 {
+            return 0.0;
+        }
+        let p = self.score();
+        let variance = (self.wins as f64 * (1.0 - p).powi(2)
+            + self.draws as f64 * (0.5 - p).powi(2)
+            + self.losses as f64 * p.powi(2))
+            / n;
+        if variance == 0.0 {
+            return 0.0;
+        }
+        let s0 = score_from_elo(sprt.elo0);
+        let s1 = score_from_elo(sprt.elo1);
+        n * (s1 - s0) * (2.0 * p - s0 - s1) / (2.0 * variance)
+    }
+
+    /// 逐次確率比検定の結論が出ていればそれを返す
+    pub fn sprt_decision(&self, sprt: &Sprt) -> Option<SprtDecision> {
+        let llr = self.llr(sprt);
+        if llr >= ((1.0 - sprt.beta) / sprt.alpha).ln() {
+            Some(SprtDecision::H1)
+        } else if llr <= (sprt.beta / (1.0 - sprt.alpha)).ln() {
+            Some(SprtDecision::H0)
+        } else {
+            None
+        }
+    }
+
+    // 1局の結果を加える
+    fn add(&mut self, discs: u32, opponent_discs: u32) {
+        if discs > opponent_discs {
+            self.wins += 1;
+        } else if discs < opponent_discs {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+        self.discs += discs;
+        self.opponent_discs += opponent_discs;
+    }
+}
+
+// 得点の期待値に対応する Elo レーティングの差を返す
+fn elo_from_score(p: f64) -> f64 {
+    // 互角の場合に -0 とならないように 0 を加える
+    -400.0 * (1.0 / p - 1.0).log10() + 0.0
+}
+
+// Elo レーティングの差に対応する得点の期待値を返す
+fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// 初期局面から plies 手打ち進めた局面のうち、評価値の絶対値が margin 以下の局面を返す
+///
+/// 盤の対称性によって同じになる局面は1つにまとめ、並びは常に同じになる
+pub fn balanced_openings(plies: i32, margin: i32) -> Vec<Game> {
+    let mut positions = vec![Game::new()];
+    for _ in 0..plies {
+        let mut seen = HashSet::new();
+        let mut next = Vec::new();
+        for game in positions {
+            let mut m = game.legal_moves();
+            while m != 0 {
+                let i = m.trailing_zeros() as i32;
+                m &= m - 1;
+                let mut child = game.clone();
+                child.play(i).unwrap();
+                if seen.insert(canonical(child.black(), child.white())) {
+                    next.push(Game::from_position(
+                        child.black(),
+                        child.white(),
+                        child.black_to_move(),
+                    ));
+                }
+            }
+        }
+        positions = next;
+    }
+    positions.retain(|game| {
+        // 打つ手のない局面は開始局面にしない
+        game.legal_moves() != 0
+            && analyze(game.myself(), game.opponent(), 3, 1)[0].score.abs() <= margin
+    });
+    positions
+}
+
+// 盤の8通りの対称変換のうち最小の表現を返す
+fn canonical(black: u64, white: u64) -> (u64, u64) {
+    let mut b = black;
+    let mut w = white;
+    let mut best = (b, w);
+    for k in 0..8 {
+        (b, w) = if k % 2 == 0 {
+            (flip_vertical(b), flip_vertical(w))
+        } else {
+            (flip_diagonal(b), flip_diagonal(w))
+        };
+        best = best.min((b, w));
+    }
+    best
+}
+
+// 盤を上下に反転する
+fn flip_vertical(x: u64) -> u64 {
+    x.swap_bytes()
+}
+
+// 盤を a1-h8 の対角線について反転する
+fn flip_diagonal(x: u64) -> u64 {
+    let mut x = x;
+    let t = 0x0F0F_0F0F_0000_0000 & (x ^ (x << 28));
+    x ^= t ^ (t >> 28);
+    let t = 0x3333_0000_3333_0000 & (x ^ (x << 14));
+    x ^= t ^ (t >> 14);
+    let t = 0x5500_5500_5500_5500 & (x ^ (x << 7));
+    x ^= t ^ (t >> 7);
+    x
+}
+
+/// 2つのエンジンを対戦させ、1つ目のエンジンから見た結果を返す
+///
+/// 開始局面は balanced_openings によって作り、各局面で先後を入れ替えて2局ずつ打つ
+///
+/// 開始局面の数より多い対局数が指定された場合は、開始局面を繰り返して異なる乱数の種で打つ
+///
+/// 逐次確率比検定の結論が出た場合は残りの対局を打たずに打ち切る
+pub fn play_match(first: Engine, second: Engine, config: &MatchConfig) -> MatchResult {
+    let openings = balanced_openings(config.opening_plies, config.opening_margin);
+    assert!(!openings.is_empty(), "no balanced opening");
+    let pairs = config.games.div_ceil(2) as usize;
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let mut result = MatchResult::default();
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..config.concurrency.max(1) {
+            let sender = sender.clone();
+            let (next, stop, openings) = (&next, &stop, &openings);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let k = next.fetch_add(1, Ordering::Relaxed);
+                    if k >= pairs * 2 {
+                        break;
+                    }
+                    let opening = &openings[(k / 2) % openings.len()];
+                    let seed = k as u64;
+                    // 偶数番目の対局では1つ目のエンジンが開始局面の手番を持つ
+                    let first_to_move = k % 2 == 0;
+                    let first_is_black = opening.black_to_move() == first_to_move;
+                    let (black, white) = if first_is_black {
+                        (first, second)
+                    } else {
+                        (second, first)
+                    };
+                    let game = play_game(opening.clone(), black, white, seed);
+                    let (b, w) = (game.black().count_ones(), game.white().count_ones());
+                    let discs = if first_is_black { (b, w) } else { (w, b) };
+                    if sender.send(discs).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for (discs, opponent_discs) in receiver {
+            result.add(discs, opponent_discs);
+            if let Some(sprt) = &config.sprt
+                && result.sprt.is_none()
+            {
+                result.sprt = result.sprt_decision(sprt);
+                if result.sprt.is_some() {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+    });
+    result
+}
+
+// 開始局面 game から終局まで対局し、終局した対局を返す
+fn play_game(mut game: Game, black: Engine, white: Engine, seed: u64) -> Game {
+    while !game.is_over() {
+        if game.must_pass() {
+            game.pass().unwrap();
+            continue;
+        }
+        let engine = if game.black_to_move() { black } else { white };
+        let index = engine(game.myself(), game.opponent(), seed);
+        game.play(index).expect("engine returned an illegal move");
+    }
+    game
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::Level;
+    use crate::{heuristic_search, perft};
+
+    #[test]
+    fn symmetry_test() {
+        assert_eq!(1 << 56, flip_vertical(1));
+        assert_eq!(1 << 8, flip_diagonal(1 << 1));
+        assert_eq!(1 << 63, flip_diagonal(1 << 63));
+        for x in [0x0123_4567_89AB_CDEF, 0x8040_2010_0804_0201] {
+            assert_eq!(x, flip_vertical(flip_vertical(x)));
+            assert_eq!(x, flip_diagonal(flip_diagonal(x)));
+        }
+        // 最初の4つの手はすべて対称なので1つにまとまる
+        let game = Game::new();
+        let mut positions = HashSet::new();
+        let mut m = game.legal_moves();
+        while m != 0 {
+            let i = m.trailing_zeros() as i32;
+            m &= m - 1;
+            let mut child = game.clone();
+            child.play(i).unwrap();
+            positions.insert(canonical(child.black(), child.white()));
+        }
+        assert_eq!(1, positions.len());
+    }
+
+    #[test]
+    fn opening_test() {
+        let openings = balanced_openings(2, i32::MAX);
+        // 2手目までの12局面は対称性によって3局面にまとまる
+        assert_eq!(12, perft(Game::new().myself(), Game::new().opponent(), 2));
+        assert_eq!(3, openings.len());
+        assert!(openings.iter().all(|g| g.ply() == 0 && g.black_to_move()));
+        assert_eq!(openings, balanced_openings(2, i32::MAX));
+        let balanced = balanced_openings(4, 300);
+        assert!(!balanced.is_empty());
+        assert!(balanced.len() < balanced_openings(4, i32::MAX).len());
+    }
+
+    #[test]
+    fn elo_test() {
+        let result = MatchResult {
+            wins: 60,
+            draws: 0,
+            losses: 40,
+            ..MatchResult::default()
+        };
+        let (elo, error) = result.elo().unwrap();
+        assert!((elo - 70.4).abs() < 0.1);
+        assert!(error > 60.0 && error < 80.0);
+        let even = MatchResult {
+            wins: 10,
+            draws: 5,
+            losses: 10,
+            ..MatchResult::default()
+        };
+        assert_eq!(0.0, even.elo().unwrap().0);
+        let sweep = MatchResult {
+            wins: 10,
+            ..MatchResult::default()
+        };
+        assert_eq!(None, sweep.elo());
+        // 大きく勝ち越すと対立仮説を、互角なら帰無仮説を採択する
+        let sprt = Sprt::default();
+        let strong = MatchResult {
+            wins: 300,
+            draws: 0,
+            losses: 200,
+            ..MatchResult::default()
+        };
+        assert_eq!(Some(SprtDecision::H1), strong.sprt_decision(&sprt));
+        let equal = MatchResult {
+            wins: 1000,
+            draws: 0,
+            losses: 1000,
+            ..MatchResult::default()
+        };
+        assert_eq!(Some(SprtDecision::H0), equal.sprt_decision(&sprt));
+        assert_eq!(None, even.sprt_decision(&sprt));
+    }
+
+    #[test]
+    fn match_test() {
+        let strong = |myself, opponent, _| heuristic_search(myself, opponent, 3);
+        let weak = |myself, opponent, seed| Level::Novice.choose_move(myself, opponent, seed);
+        let config = MatchConfig {
+            games: 12,
+            concurrency: 2,
+            opening_plies: 4,
+            opening_margin: 2000,
+            sprt: None,
+        };
+        let result = play_match(&strong, &weak, &config);
+        assert_eq!(12, result.games());
+        assert!(result.wins > result.losses);
+        assert!(result.discs + result.opponent_discs <= 64 * 12);
+        // 先後を入れ替えて打つので、同じエンジンどうしの対戦は勝敗が釣り合う
+        let result = play_match(&strong, &strong, &config);
+        assert_eq!(result.wins, result.losses);
+        assert_eq!(result.discs, result.opponent_discs);
+        // 結論が出れば打ち切る
+        let config = MatchConfig {
+            games: 1000,
+            sprt: Some(Sprt {
+                elo0: 0.0,
+                elo1: 200.0,
+                alpha: 0.1,
+                beta: 0.1,
+            }),
+            ..config
+        };
+        let result = play_match(&strong, &weak, &config);
+        assert_eq!(Some(SprtDecision::H1), result.sprt);
+        assert!(result.games() < 1000);
+    }
+}
@@ -0,0 +1,88 @@
+use std::ffi::{CStr, c_char};
+
+/// C から呼び出せる関数の互換性を表す ABI のバージョン
+///
+/// 既存の関数のシグネチャや意味を互換性のない形で変更した場合に増やす（関数や定数の追加では増やさない）
+pub const REVERSI_ABI_VERSION: u32 = 1;
+
+/// 複数スレッドによる並列探索に対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_PARALLEL_SEARCH: u64 = 0x0001;
+/// 時間を制限した探索に対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_TIMED_SEARCH: u64 = 0x0002;
+/// 定石集に対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_BOOK: u64 = 0x0004;
+/// モンテカルロ木探索に対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_MCTS: u64 = 0x0008;
+/// 相手の手番での先読みに対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_PONDER: u64 = 0x0010;
+/// 設定のハンドルに対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_CONFIG: u64 = 0x0020;
+/// 状態を持つエンジンのハンドルと探索の中断に対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_ENGINE: u64 = 0x0040;
+/// 入力を検査してエラーの戻り値を返す関数に対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_CHECKED: u64 = 0x0080;
+/// 探索の途中経過を受け取るコールバックに対応していることを表す機能ビット
+pub const REVERSI_CAPABILITY_PROGRESS: u64 = 0x0100;
+
+// このライブラリのバージョンを表す NUL 終端の文字列
+const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+
+/// このライブラリのバージョンを返す
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// このライブラリが対応している機能の機能ビットの論理和を返す
+pub fn capabilities() -> u64 {
+    REVERSI_CAPABILITY_PARALLEL_SEARCH
+        | REVERSI_CAPABILITY_TIMED_SEARCH
+        | REVERSI_CAPABILITY_MCTS
+        | REVERSI_CAPABILITY_PONDER
+        | REVERSI_CAPABILITY_CONFIG
+        | REVERSI_CAPABILITY_ENGINE
+        | REVERSI_CAPABILITY_CHECKED
+        | REVERSI_CAPABILITY_PROGRESS
+}
+
+/// このライブラリのバージョンを "1.0.2" のような NUL 終端の文字列で返す
+///
+/// 返される文字列は静的な領域にあり、解放する必要はない
+#[unsafe(no_mangle)]
+pub extern "C" fn reversi_version() -> *const c_char {
+    CStr::from_bytes_with_nul(VERSION.as_bytes())
+        .unwrap()
+        .as_ptr()
+}
+
+/// このライブラリの ABI のバージョンを返す
+///
+/// ヘッダーの REVERSI_ABI_VERSION と異なる場合は、ヘッダーとライブラリに互換性がない
+#[unsafe(no_mangle)]
+pub extern "C" fn reversi_abi_version() -> u32 {
+    REVERSI_ABI_VERSION
+}
+
+/// このライブラリが対応している機能を REVERSI_CAPABILITY_* の論理和で返す
+///
+/// ヘッダーにある機能ビットが立っていない場合は、その機能の関数を呼び出してはならない
+#[unsafe(no_mangle)]
+pub extern "C" fn reversi_capabilities() -> u64 {
+    capabilities()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_test() {
+        let version = unsafe { CStr::from_ptr(reversi_version()) };
+        assert_eq!(env!("CARGO_PKG_VERSION"), version.to_str().unwrap());
+        assert_eq!(version.to_str().unwrap(), super::version());
+        assert_eq!(REVERSI_ABI_VERSION, reversi_abi_version());
+        let capabilities = reversi_capabilities();
+        assert_ne!(0, capabilities & REVERSI_CAPABILITY_PARALLEL_SEARCH);
+        assert_ne!(0, capabilities & REVERSI_CAPABILITY_ENGINE);
+        assert_eq!(0, capabilities & REVERSI_CAPABILITY_BOOK);
+    }
+}
@@ -0,0 +1,139 @@
+// 共有ライブラリを生成されたヘッダー経由で C から呼び出す結合テスト
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "reversi_ai.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                   \
+    do {                                                              \
+        if (!(cond)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,    \
+                    __LINE__, #cond);                                 \
+            failures++;                                               \
+        }                                                             \
+    } while (0)
+
+// 初期局面（黒の手番）
+static const uint64_t BLACK = 0x0000000810000000ULL;
+static const uint64_t WHITE = 0x0000001008000000ULL;
+
+static void test_version(void) {
+    CHECK(reversi_abi_version() == REVERSI_ABI_VERSION);
+    CHECK(strlen(reversi_version()) > 0);
+    CHECK(reversi_capabilities() & REVERSI_CAPABILITY_ENGINE);
+}
+
+static void test_board(void) {
+    CHECK(index_to_bit(0) == 1);
+    CHECK(index_to_bit(64) == 0);
+    CHECK(position_to_index(3, 2) == 19);
+    CHECK(possible_moves(BLACK, WHITE) == 0x0000102004080000ULL);
+    CHECK(count_bits(possible_moves(BLACK, WHITE)) == 4);
+    uint64_t s = 0, o = 0;
+    uint64_t turns = place(BLACK, WHITE, 19, &s, &o);
+    CHECK(turns == 0x0000000008000000ULL);
+    CHECK(count_bits(s) == 4 && count_bits(o) == 1);
+    CHECK(perft(BLACK, WHITE, 4) == 244);
+}
+
+static void test_search(void) {
+    int32_t move = choose_move(BLACK, WHITE);
+    CHECK(can_place(BLACK, WHITE, move));
+    CHECK(heuristic_search(BLACK, WHITE, 3) ==
+          heuristic_search_parallel_with(BLACK, WHITE, 3, 2));
+    // 空きマスが2つの終盤の局面
+    uint64_t black = 0x00000000FFFFFFFCULL, white = 0xFFFFFFFF00000000ULL;
+    int32_t score = 0, bound = 0;
+    CHECK(full_search_window(black, white, -64, 64, &score) ==
+          REVERSI_WINDOW_EXACT);
+    CHECK(full_search_window(black, white, score + 1, 64, &bound) ==
+          REVERSI_WINDOW_FAIL_LOW);
+    uint64_t visits[64] = {0};
+    move = mcts_choose_move(BLACK, WHITE, 200, 0, 1, 0, 1, visits);
+    CHECK(can_place(BLACK, WHITE, move));
+    CHECK(visits[move] > 0);
+}
+
+static void test_checked(void) {
+    uint64_t bit = 0;
+    int32_t move = 0;
+    CHECK(index_to_bit_checked(63, &bit) == REVERSI_OK);
+    CHECK(bit == 0x8000000000000000ULL);
+    CHECK(index_to_bit_checked(64, &bit) == REVERSI_ERROR_INVALID_INDEX);
+    CHECK(index_to_bit_checked(0, NULL) == REVERSI_ERROR_NULL_POINTER);
+    CHECK(choose_move_checked(BLACK, BLACK, &move) ==
+          REVERSI_ERROR_OVERLAPPING_BOARDS);
+    CHECK(heuristic_search_checked(BLACK, WHITE, 0, &move) ==
+          REVERSI_ERROR_INVALID_DEPTH);
+    CHECK(choose_move_parallel_with_checked(BLACK, WHITE, 0, &move) ==
+          REVERSI_ERROR_INVALID_CONCURRENCY);
+    uint64_t s = 0, o = 0;
+    CHECK(place_checked(BLACK, WHITE, 0, &s, &o, NULL) ==
+          REVERSI_ERROR_ILLEGAL_MOVE);
+    CHECK(place_checked(BLACK, WHITE, 19, &s, &o, NULL) == REVERSI_OK);
+    int32_t result = 0, score = 0;
+    CHECK(full_search_window_checked(BLACK, WHITE, 1, 0, &result, &score) ==
+          REVERSI_ERROR_INVALID_WINDOW);
+}
+
+static void test_handles(void) {
+    EngineConfig *config = engine_config_new();
+    int32_t value = 0;
+    CHECK(engine_config_get(config, "wide_depth", &value) == 1 && value == 5);
+    CHECK(engine_config_set(config, "wide_depth", 3) == 1);
+    CHECK(engine_config_set(config, "narrow_depth", 3) == 1);
+    CHECK(engine_config_set(config, "unknown", 3) == 0);
+    CHECK(choose_move_with_config(config, BLACK, WHITE) ==
+          heuristic_search(BLACK, WHITE, 3));
+    engine_config_free(config);
+
+    SearchEngine *engine = engine_new();
+    CHECK(engine_set_option(engine, "concurrency", 2) == 1);
+    CHECK(engine_choose_move(engine, BLACK, WHITE) ==
+          choose_move_parallel_with(BLACK, WHITE, 2));
+    int32_t scores[64];
+    CHECK(engine_analyze(engine, BLACK, WHITE, 3, scores) == 4);
+    CHECK(scores[19] != -2147483647 && scores[0] == -2147483647);
+    engine_free(engine);
+
+    Ponder *ponder = ponder_start(WHITE, BLACK, -1, 1);
+    int32_t predicted = ponder_predicted_move(ponder);
+    CHECK(can_place(BLACK, WHITE, predicted));
+    ponder_cancel(ponder);
+}
+
+static void record_progress(int32_t depth, int32_t best_move, int32_t score,
+                            uint64_t nodes, void *user_data) {
+    (void)depth;
+    (void)score;
+    (void)nodes;
+    *(int32_t *)user_data = best_move;
+}
+
+static void test_progress(void) {
+    CHECK(reversi_capabilities() & REVERSI_CAPABILITY_PROGRESS);
+    int32_t reported = -1;
+    int32_t move = choose_move_with_progress(BLACK, WHITE, 1, 1000,
+                                             record_progress, &reported);
+    CHECK(move == choose_move_parallel_with(BLACK, WHITE, 1));
+    CHECK(reported == move);
+    CHECK(choose_move_with_progress(BLACK, WHITE, 1, 0, NULL, NULL) == move);
+}
+
+int main(void) {
+    test_version();
+    test_board();
+    test_search();
+    test_checked();
+    test_handles();
+    test_progress();
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
@@ -0,0 +1,50 @@
+//! 共有ライブラリを生成されたヘッダーとともに C のプログラムから使う結合テスト
+//!
+//! C コンパイラ（環境変数 CC、なければ cc）が必要
+
+#![cfg(unix)]
+
+use std::env;
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// テストと同じビルドで生成された共有ライブラリのあるディレクトリを返す
+fn library_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+    let name = format!("{DLL_PREFIX}reversi_ai{DLL_SUFFIX}");
+    [deps, deps.parent().unwrap()]
+        .into_iter()
+        .find(|dir| dir.join(&name).exists())
+        .unwrap_or_else(|| panic!("{name} is not built"))
+        .to_path_buf()
+}
+
+#[test]
+fn c_api_test() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib = library_dir();
+    let out = env::temp_dir().join(format!("reversi_ai_c_api_{}", std::process::id()));
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&out)
+        .arg(root.join("tests/c_api.c"))
+        .arg("-I")
+        .arg(root.join("include"))
+        .arg("-L")
+        .arg(&lib)
+        .arg("-lreversi_ai")
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "failed to compile tests/c_api.c");
+    let path_var = if cfg!(target_os = "macos") {
+        "DYLD_LIBRARY_PATH"
+    } else {
+        "LD_LIBRARY_PATH"
+    };
+    let status = Command::new(&out).env(path_var, &lib).status().unwrap();
+    std::fs::remove_file(&out).unwrap();
+    assert!(status.success(), "tests/c_api.c failed");
+}